@@ -79,11 +79,10 @@ function parser(regionMap) {
   const regions = [...regionMap.values()];
 
   return [
-    `impl FromStr for Region {`,
-    `    type Err = RegionError;`,
-    ``,
-    `    fn from_str(s: &str) -> Result<Self, Self::Err> {`,
-    `        match s {`,
+    `impl Region {`,
+    `    /// Look up a [\`Region\`] by its exact (lowercase) code.`,
+    `    fn from_code(code: &str) -> Result<Self, RegionError> {`,
+    `        match code {`,
     ...regions.map(
       (region) =>
         `            ${repr(region.code)} => Ok(Self::${key(region)}),`
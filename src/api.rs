@@ -2,10 +2,15 @@
 //!
 //! [Machines API]: https://fly.io/docs/machines/api/
 
-use std::net::Ipv6Addr;
+use std::{
+    net::{IpAddr, Ipv6Addr},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
 use http::header;
-use reqwest::{IntoUrl, Method, RequestBuilder, Url};
+use reqwest::{IntoUrl, Method, RequestBuilder, StatusCode, Url};
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "environment")]
@@ -41,6 +46,8 @@ pub struct Client {
     http: reqwest::Client,
     origin: Url,
     token: String,
+    retry: Option<RetryConfig>,
+    timeout: Duration,
 }
 
 impl Client {
@@ -50,6 +57,21 @@ impl Client {
     pub const USER_AGENT: &'static str =
         concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
+    /// How long a request may take, if no other [timeout][Client::with_timeout]
+    /// is set.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// How often [`wait_for_state`][Client::wait_for_state] checks a
+    /// machine's state when polling.
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    /// The states the Machines API `wait` endpoint accepts.
+    const WAIT_STATES: [MachineState; 3] = [
+        MachineState::Started,
+        MachineState::Stopped,
+        MachineState::Destroyed,
+    ];
+
     /// Create a [Client] for the Machines API. An [authentication token][] is
     /// required.
     ///
@@ -65,57 +87,370 @@ impl Client {
         Self::with_origin(Self::default_origin(), token)
     }
 
+    /// Create a [Client] using the `$FLY_API_TOKEN` [environment variable][],
+    /// like [`Config::client`][crate::Config::client]. Requests are sent to
+    /// `$FLYTRAP_API_ORIGIN` if set, or otherwise to the same origin as
+    /// [`Client::new`].
+    ///
+    /// Unlike [`Client::default`], returns an `Unavailable` [error][Error]
+    /// instead of panicking if `$FLY_API_TOKEN` is unset (or a `Url` error if
+    /// `$FLYTRAP_API_ORIGIN` is invalid).
+    ///
+    /// [environment variable]: https://fly.io/docs/reference/runtime-environment/#environment-variables
+    #[cfg(feature = "environment")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
+    pub fn try_default() -> Result<Self, Error> {
+        Self::from_config(crate::Config::from_env())
+    }
+
+    #[cfg(feature = "environment")]
+    fn from_config(mut config: crate::Config) -> Result<Self, Error> {
+        #[cfg(feature = "detect")]
+        if config.private_ip.is_none() {
+            config.private_ip = crate::placement::detect_address();
+        }
+
+        config.client()
+    }
+
     /// Create a [Client] which sends API Requests to the given `origin`.
+    ///
+    /// # Panics
+    ///
+    /// If `origin` is not a valid base URL; see [`Client::try_with_origin`].
     pub fn with_origin(origin: impl IntoUrl, token: impl Into<String>) -> Self {
         Self::with_client(Default::default(), origin, token)
     }
 
+    /// Create a [Client] which sends API Requests to the given `origin`, or
+    /// return a `Url` [error][Error] if `origin` is not a valid base URL.
+    ///
+    /// ```
+    /// use flytrap::api::Client;
+    ///
+    /// assert!(Client::try_with_origin("http://localhost:4280", "token").is_ok());
+    /// assert!(Client::try_with_origin("not a url", "token").is_err());
+    /// ```
+    pub fn try_with_origin(origin: impl IntoUrl, token: impl Into<String>) -> Result<Self, Error> {
+        Self::try_with_client(Default::default(), origin, token)
+    }
+
     /// Create a [Client] wrapping an explicit [`reqwest::Client`].
+    ///
+    /// # Panics
+    ///
+    /// If `origin` is not a valid base URL; see [`Client::try_with_client`].
     pub fn with_client(
         http_client: reqwest::Client,
         origin: impl IntoUrl,
         token: impl Into<String>,
     ) -> Self {
-        Self {
+        Self::try_with_client(http_client, origin, token)
+            .expect("invalid Fly.io Machines API base URL")
+    }
+
+    /// Create a [Client] wrapping an explicit [`reqwest::Client`], or return
+    /// a `Url` [error][Error] if `origin` is not a valid base URL.
+    pub fn try_with_client(
+        http_client: reqwest::Client,
+        origin: impl IntoUrl,
+        token: impl Into<String>,
+    ) -> Result<Self, Error> {
+        // reqwest rejects URLs without a host (which includes every URL that
+        // can't be a base) without an underlying parse error.
+        let origin = origin.into_url().map_err(|err| {
+            let source = std::error::Error::source(&err).and_then(|e| e.downcast_ref());
+            Error::Url(
+                source
+                    .copied()
+                    .unwrap_or(url::ParseError::RelativeUrlWithCannotBeABaseBase),
+            )
+        })?;
+
+        Ok(Self {
             http: http_client,
-            origin: origin
-                .into_url()
-                .expect("invalid Fly.io Machines API base URL"),
+            origin,
             token: token.into(),
+            retry: None,
+            timeout: Self::DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Fail requests which take longer than `timeout` to complete (by default,
+    /// [30 seconds][Client::DEFAULT_TIMEOUT]) with a `Timeout` [error][Error].
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Retry requests which fail with `429 Too Many Requests` or a `5xx`
+    /// server error, according to the given [policy][RetryConfig].
+    ///
+    /// ```
+    /// use flytrap::api::{Client, RetryConfig};
+    ///
+    /// let client = Client::with_origin(Client::PUBLIC_ORIGIN, "token")
+    ///     .with_retry(RetryConfig::default());
+    /// ```
+    pub fn with_retry(self, retry: RetryConfig) -> Self {
+        Self {
+            retry: Some(retry),
+            ..self
         }
     }
 
     /// List the Fly.io [apps][AppEntry] under the given `organization`.
+    ///
+    /// Only the first page of apps is returned; see [`apps_all`][Self::apps_all]
+    /// or [`apps_page`][Self::apps_page] for organizations with many apps.
     pub async fn apps(&self, organization: impl AsRef<str>) -> Result<OrganizationApps, Error> {
-        self.request(Method::GET, "/v1/apps")
+        self.apps_page(organization, None).await
+    }
+
+    /// List every Fly.io [app][AppEntry] under the given `organization`,
+    /// following [pages][Self::apps_page] until none remain.
+    ///
+    /// The `total` reported with the first page is preserved.
+    pub async fn apps_all(&self, organization: impl AsRef<str>) -> Result<OrganizationApps, Error> {
+        let organization = organization.as_ref();
+
+        let mut all = self.apps_page(organization, None).await?;
+        while let Some(cursor) = all.next_cursor.take() {
+            let page = self.apps_page(organization, Some(&cursor)).await?;
+
+            all.apps.extend(page.apps);
+            all.next_cursor = page.next_cursor;
+        }
+
+        Ok(all)
+    }
+
+    /// List one page of the Fly.io [apps][AppEntry] under the given
+    /// `organization`, starting at `cursor` (or the first page if `None`).
+    ///
+    /// The page's [`next_cursor`][OrganizationApps::next_cursor] locates the
+    /// following page, if any.
+    pub async fn apps_page(
+        &self,
+        organization: impl AsRef<str>,
+        cursor: Option<&str>,
+    ) -> Result<OrganizationApps, Error> {
+        let organization = organization.as_ref();
+        let request = self
+            .request(Method::GET, "/v1/apps")?
             .query(&OrganizationAppsQuery {
-                organization: organization.as_ref(),
-            })
-            .send()
+                organization,
+                cursor,
+            });
+        let response = self.send(request).await;
+
+        Self::check(response, format!("/v1/apps?org_slug={organization}"))?
+            .json()
             .await
-            .map_err(Error::from)?
-            .error_for_status()
-            .map_err(Error::from)?
+            .map_err(Error::from)
+    }
+
+    /// Get the details of the Fly.io [app][App] with the given `name`.
+    ///
+    /// If the app doesn't exist, a `NotFound` [error][Error] is returned.
+    pub async fn app(&self, name: impl AsRef<str>) -> Result<App, Error> {
+        let path = format!("/v1/apps/{}", name.as_ref());
+        let response = self.send(self.request(Method::GET, &path)?).await;
+
+        Self::check(response, path)?
             .json()
             .await
             .map_err(Error::from)
     }
 
     /// List Fly.io [machines][Machine] for the given `app`.
+    ///
+    /// If the app doesn't exist, a `NotFound` [error][Error] is returned.
     pub async fn machines(&self, app: impl AsRef<str>) -> Result<Vec<Machine>, Error> {
+        self.machines_with(app, &MachinesQuery::new()).await
+    }
+
+    /// List Fly.io [machines][Machine] for the given `app`, filtered by the
+    /// Machines API according to `query`.
+    ///
+    /// ```no_run
+    /// use flytrap::api::{Client, MachinesQuery};
+    ///
+    /// # async fn run(client: Client) -> Result<(), flytrap::Error> {
+    /// let query = MachinesQuery::new()
+    ///     .include_deleted(true)
+    ///     .metadata("fly_process_group", "worker");
+    ///
+    /// for machine in client.machines_with("flytrap", &query).await? {
+    ///     println!("{} is {:?}", machine.name, machine.state);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn machines_with(
+        &self,
+        app: impl AsRef<str>,
+        query: &MachinesQuery,
+    ) -> Result<Vec<Machine>, Error> {
         let app = app.as_ref();
 
-        self.request(Method::GET, format!("/v1/apps/{app}/machines"))
-            .send()
+        let path = format!("/v1/apps/{app}/machines");
+        let request = self.request(Method::GET, &path)?.query(&query.pairs());
+        let response = self.send(request).await;
+
+        Self::check(response, path)?
+            .json()
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Get the [machine][Machine] with the given `id`, belonging to `app`.
+    ///
+    /// If the machine doesn't exist, a `NotFound` [error][Error] is returned.
+    pub async fn machine(
+        &self,
+        app: impl AsRef<str>,
+        id: impl AsRef<str>,
+    ) -> Result<Machine, Error> {
+        let path = format!("/v1/apps/{}/machines/{}", app.as_ref(), id.as_ref());
+
+        let response = self.send(self.request(Method::GET, &path)?).await;
+
+        Self::check(response, path)?
+            .json()
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Wait until the [machine][Machine] with the given `id`, belonging to
+    /// `app`, reaches the `target` state, and return it.
+    ///
+    /// Transitional states (like `Starting` on the way to `Started`) are
+    /// waited through. If the machine hasn't reached `target` within
+    /// `timeout`, a `Timeout` [error][Error] is returned; if it reaches a
+    /// [terminal][MachineState::is_terminal] state it can't leave for
+    /// `target`, a `State` error is returned.
+    ///
+    /// When `target` is `Started`, `Stopped`, or `Destroyed`, the Machines API
+    /// [wait][] endpoint is used; otherwise (or if the endpoint rejects the
+    /// request), the machine is polled until its state matches. The API never
+    /// reports the `Unknown` state, so waiting for it returns a `State` error
+    /// without sending any request.
+    ///
+    /// [wait]: https://fly.io/docs/machines/api/machines-resource/#wait-for-a-machine-to-reach-a-specified-state
+    pub async fn wait_for_state(
+        &self,
+        app: impl AsRef<str>,
+        id: impl AsRef<str>,
+        target: MachineState,
+        timeout: Duration,
+    ) -> Result<Machine, Error> {
+        let (app, id) = (app.as_ref(), id.as_ref());
+        let deadline = Instant::now() + timeout;
+
+        if target == MachineState::Unknown {
+            return Err(Error::State(target));
+        }
+
+        if Self::WAIT_STATES.contains(&target) {
+            if let Some(machine) = self.wait_native(app, id, target, deadline).await? {
+                return Ok(machine);
+            }
+        }
+
+        loop {
+            let machine = self.machine(app, id).await?;
+            if machine.state == target {
+                return Ok(machine);
+            }
+            if machine.state.is_terminal() && machine.state.target() != Some(target) {
+                return Err(Error::State(target));
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Error::Timeout);
+            }
+
+            tokio::time::sleep(remaining.min(Self::POLL_INTERVAL)).await;
+        }
+    }
+
+    /// Start the stopped [machine][Machine] with the given `id`, belonging to
+    /// `app`.
+    pub async fn start_machine(
+        &self,
+        app: impl AsRef<str>,
+        id: impl AsRef<str>,
+    ) -> Result<(), Error> {
+        self.machine_action(app.as_ref(), id.as_ref(), "start")
+            .await
+    }
+
+    /// Stop the running [machine][Machine] with the given `id`, belonging to
+    /// `app`.
+    pub async fn stop_machine(
+        &self,
+        app: impl AsRef<str>,
+        id: impl AsRef<str>,
+    ) -> Result<(), Error> {
+        self.machine_action(app.as_ref(), id.as_ref(), "stop").await
+    }
+
+    /// Restart the running [machine][Machine] with the given `id`, belonging
+    /// to `app`.
+    pub async fn restart_machine(
+        &self,
+        app: impl AsRef<str>,
+        id: impl AsRef<str>,
+    ) -> Result<(), Error> {
+        self.machine_action(app.as_ref(), id.as_ref(), "restart")
             .await
-            .map_err(Error::from)?
-            .error_for_status()
-            .map_err(Error::from)?
+    }
+
+    /// List the [events][MachineEvent] in the history of the machine with the
+    /// given `id`, belonging to `app`.
+    ///
+    /// If the machine doesn't exist, a `NotFound` [error][Error] is returned.
+    pub async fn machine_events(
+        &self,
+        app: impl AsRef<str>,
+        id: impl AsRef<str>,
+    ) -> Result<Vec<MachineEvent>, Error> {
+        let path = format!("/v1/apps/{}/machines/{}/events", app.as_ref(), id.as_ref());
+        let response = self.send(self.request(Method::GET, &path)?).await;
+
+        Self::check(response, path)?
             .json()
             .await
             .map_err(Error::from)
     }
 
+    /// List the [volumes][Volume] belonging to `app`.
+    ///
+    /// If the app doesn't exist, a `NotFound` [error][Error] is returned.
+    pub async fn volumes(&self, app: impl AsRef<str>) -> Result<Vec<Volume>, Error> {
+        let path = format!("/v1/apps/{}/volumes", app.as_ref());
+        let response = self.send(self.request(Method::GET, &path)?).await;
+
+        Self::check(response, path)?
+            .json()
+            .await
+            .map_err(Error::from)
+    }
+
+    /// List the public [IP addresses][IpAssignment] allocated to `app`.
+    pub async fn ip_assignments(&self, app: impl AsRef<str>) -> Result<Vec<IpAssignment>, Error> {
+        let path = format!("/v1/apps/{}/ip_assignments", app.as_ref());
+        let response = self.send(self.request(Method::GET, &path)?).await;
+
+        let assignments: IpAssignments = Self::check(response, path)?
+            .json()
+            .await
+            .map_err(Error::from)?;
+
+        Ok(assignments.ips)
+    }
+
     /// List Fly.io [machines][Machine] for the current app, excluding the
     /// current machine.
     #[cfg(feature = "environment")]
@@ -133,19 +468,112 @@ impl Client {
         Ok(machines)
     }
 
-    fn request(&self, method: Method, url: impl AsRef<str>) -> RequestBuilder {
-        let url = self
-            .origin
-            .join(url.as_ref())
-            .expect("invalid Machines API request URL");
+    /// Wait for a machine to reach `target` using the Machines API `/wait`
+    /// endpoint. Returns `None` if the endpoint couldn't handle the request.
+    async fn wait_native(
+        &self,
+        app: &str,
+        id: &str,
+        target: MachineState,
+        deadline: Instant,
+    ) -> Result<Option<Machine>, Error> {
+        let path = format!("/v1/apps/{app}/machines/{id}/wait");
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Error::Timeout);
+            }
+            let seconds = remaining.as_secs().clamp(1, 60);
+
+            let request = self
+                .request(Method::GET, &path)?
+                // the API holds the request open for up to `timeout` seconds
+                .timeout(self.timeout + Duration::from_secs(seconds))
+                .query(&[
+                    ("state", target.as_str()),
+                    ("timeout", &seconds.to_string()),
+                ]);
+            let response = self.send(request).await.map_err(Error::from)?;
+
+            match response.status() {
+                status if status.is_success() => return self.machine(app, id).await.map(Some),
+                StatusCode::REQUEST_TIMEOUT => continue,
+                StatusCode::NOT_FOUND => return Err(Error::NotFound { resource: path }),
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    async fn machine_action(&self, app: &str, id: &str, action: &str) -> Result<(), Error> {
+        let path = format!("/v1/apps/{app}/machines/{id}/{action}");
+        let response = self.send(self.request(Method::POST, &path)?).await;
+
+        Self::check(response, path).map(|_| ())
+    }
+
+    /// Send a request, retrying it according to the [`RetryConfig`], if any.
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, reqwest::Error> {
+        let Some(ref retry) = self.retry else {
+            return request.send().await;
+        };
+
+        let request = request.build()?;
+        let retryable =
+            retry.all_methods || matches!(*request.method(), Method::GET | Method::HEAD);
+
+        let mut attempt = 1;
+        loop {
+            let next = match request.try_clone() {
+                Some(next) if retryable && attempt < retry.max_attempts => next,
+                _ => return self.http.execute(request).await,
+            };
+
+            let response = self.http.execute(next).await?;
+            let status = response.status();
+            if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                return Ok(response);
+            }
+
+            let retry_after = response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
 
-        self.http
+            tokio::time::sleep(retry.delay(attempt, retry_after)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Map a `404` response to `NotFound`, and any other unsuccessful status
+    /// to an `Api` [error][Error].
+    fn check(
+        response: Result<reqwest::Response, reqwest::Error>,
+        resource: String,
+    ) -> Result<reqwest::Response, Error> {
+        let response = response.map_err(Error::from)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(Error::NotFound { resource });
+        }
+
+        response.error_for_status().map_err(Error::from)
+    }
+
+    fn request(&self, method: Method, url: impl AsRef<str>) -> Result<RequestBuilder, Error> {
+        let url = self.origin.join(url.as_ref()).map_err(Error::from)?;
+
+        Ok(self
+            .http
             .request(method, url)
+            .timeout(self.timeout)
             .header(
                 header::AUTHORIZATION,
                 format!("Bearer {}", self.token.as_str()),
             )
-            .header(header::USER_AGENT, Self::USER_AGENT)
+            .header(header::USER_AGENT, Self::USER_AGENT))
     }
 
     #[cfg(feature = "environment")]
@@ -162,13 +590,55 @@ impl Client {
 #[cfg(feature = "environment")]
 #[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
 impl Default for Client {
+    /// Create a [Client] using the `$FLY_API_TOKEN` environment variable.
+    ///
+    /// # Panics
+    ///
+    /// If `$FLY_API_TOKEN` is unset or `$FLYTRAP_API_ORIGIN` is invalid; see
+    /// [`Client::try_default`].
     fn default() -> Self {
-        let token = std::env::var("FLY_API_TOKEN").expect("$FLY_API_TOKEN not set");
+        Self::try_default().expect("$FLY_API_TOKEN not set, or $FLYTRAP_API_ORIGIN invalid")
+    }
+}
+
+/// How a [`Client`] [retries][Client::with_retry] requests which fail with
+/// `429 Too Many Requests` or a `5xx` server error.
+///
+/// The delay before each retry doubles, starting from `initial_backoff`, up
+/// to `max_backoff`. If the response has a `Retry-After` header (in seconds),
+/// that delay is used instead, still limited to `max_backoff`.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct RetryConfig {
+    /// The maximum number of times a request will be sent, including the
+    /// first attempt.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The longest delay between attempts.
+    pub max_backoff: Duration,
+    /// Retry requests of any method, not only `GET` and `HEAD` requests.
+    pub all_methods: bool,
+}
 
+impl RetryConfig {
+    /// The delay before retrying after the given (1-based) `attempt` failed.
+    fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = || {
+            self.initial_backoff
+                .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        };
+
+        retry_after.unwrap_or_else(backoff).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
         Self {
-            http: Default::default(),
-            origin: Self::default_origin(),
-            token,
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            all_methods: false,
         }
     }
 }
@@ -179,6 +649,9 @@ pub struct OrganizationApps {
     #[serde(rename = "total_apps")]
     pub total: usize,
     pub apps: Vec<AppEntry>,
+    /// The cursor for the next page of apps, if there are more.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl OrganizationApps {
@@ -207,10 +680,110 @@ pub struct AppEntry {
     pub network_name: String,
 }
 
+/// A Fly.io application, as returned by [`Client::app`].
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct App {
+    pub id: String,
+    pub name: String,
+    pub organization: AppOrganization,
+    /// The app's status, like `deployed` or `suspended`.
+    #[serde(default)]
+    pub status: String,
+    #[serde(rename = "network", default)]
+    pub network_name: Option<String>,
+}
+
+/// The organization which owns an [`App`].
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct AppOrganization {
+    pub name: String,
+    pub slug: String,
+}
+
 #[derive(Serialize, Debug)]
 struct OrganizationAppsQuery<'a> {
     #[serde(rename = "org_slug")]
     pub organization: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<&'a str>,
+}
+
+/// A public IP address allocated to a Fly.io app, as returned by
+/// [`Client::ip_assignments`].
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct IpAssignment {
+    pub ip: IpAddr,
+    /// The region the address is announced from, or `global`.
+    #[serde(default)]
+    pub region: String,
+    #[serde(default)]
+    pub service_name: String,
+    /// Whether the address is shared with other apps (rather than dedicated).
+    #[serde(default)]
+    pub shared: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct IpAssignments {
+    #[serde(default)]
+    ips: Vec<IpAssignment>,
+}
+
+/// Filters for [`Client::machines_with`].
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct MachinesQuery {
+    include_deleted: bool,
+    region: Option<Location>,
+    metadata: Vec<(String, String)>,
+}
+
+impl MachinesQuery {
+    /// Create a query matching all non-deleted machines.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include machines which have been destroyed.
+    pub fn include_deleted(self, include_deleted: bool) -> Self {
+        Self {
+            include_deleted,
+            ..self
+        }
+    }
+
+    /// Only list machines placed in the given `region`.
+    pub fn region(self, region: impl Into<Location>) -> Self {
+        Self {
+            region: Some(region.into()),
+            ..self
+        }
+    }
+
+    /// Only list machines whose metadata has `key` set to `value`. May be
+    /// given more than once, to match several keys.
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    fn pairs(&self) -> Vec<(String, String)> {
+        let mut pairs = Vec::new();
+
+        if self.include_deleted {
+            pairs.push(("include_deleted".to_owned(), "true".to_owned()));
+        }
+        if let Some(ref region) = self.region {
+            pairs.push(("region".to_owned(), region.to_string()));
+        }
+        for (key, value) in &self.metadata {
+            pairs.push((format!("metadata.{key}"), value.clone()));
+        }
+
+        pairs
+    }
 }
 
 /// A Fly.io [machine][].
@@ -230,7 +803,16 @@ pub struct Machine {
     /// Every Update request potentially changes the `instance_id`.
     pub instance_id: String,
     pub private_ip: Ipv6Addr,
-    // TODO: created_at, updated_At
+    /// When the machine was created.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the machine was last updated.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub checks: Vec<MachineCheckState>,
     #[serde(default)]
@@ -259,6 +841,61 @@ impl Machine {
     }
 }
 
+/// A Fly.io [volume][], as returned by [`Client::volumes`].
+///
+/// [volume]: https://fly.io/docs/machines/api/volumes-resource/
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct Volume {
+    pub id: String,
+    pub name: String,
+    /// The volume's state, like `created` or `destroyed`.
+    pub state: String,
+    #[serde(rename = "region")]
+    pub location: Location,
+    pub size_gb: u32,
+    /// The ID of the [machine][Machine] the volume is attached to, if any.
+    #[serde(default)]
+    pub attached_machine_id: Option<String>,
+}
+
+impl Volume {
+    /// Checks if this volume is attached to a [machine][Machine].
+    pub fn is_attached(&self) -> bool {
+        self.attached_machine_id.is_some()
+    }
+
+    /// Return this volume’s [`Region`], if its region code was recognized.
+    #[cfg(feature = "regions")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "regions")))]
+    pub const fn region(&self) -> Option<Region> {
+        self.location.region()
+    }
+}
+
+/// An event in the history of a Fly.io [machine][Machine], as returned by
+/// [`Client::machine_events`].
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct MachineEvent {
+    /// What happened to the machine, like `launch`, `start`, or `exit`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The machine's status after the event, like `created` or `started`.
+    pub status: String,
+    /// What caused the event, like `user` or `flyd`.
+    pub source: String,
+    /// When the event occurred, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl MachineEvent {
+    /// When the event occurred.
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp)
+    }
+}
+
 /// The [state] of a Fly.io [machine][Machine].
 ///
 /// [state]: https://fly.io/docs/machines/machine-states/
@@ -281,6 +918,10 @@ pub enum MachineState {
     Destroying,
     /// No longer exists
     Destroyed,
+    /// A state this version of flytrap doesn't recognize (like a state Fly.io
+    /// has added since)
+    #[serde(other)]
+    Unknown,
 }
 
 impl MachineState {
@@ -311,12 +952,40 @@ impl MachineState {
         }
     }
 
+    /// The name of the state in the Machines API, like `started`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Starting => "starting",
+            Self::Started => "started",
+            Self::Stopping => "stopping",
+            Self::Stopped => "stopped",
+            Self::Replacing => "replacing",
+            Self::Destroying => "destroying",
+            Self::Destroyed => "destroyed",
+            Self::Unknown => "unknown",
+        }
+    }
+
     /// Check if the state is an in-progress transition to
     /// [another state][Self::target()].
     #[inline]
     pub const fn is_transition(&self) -> bool {
         self.target().is_some()
     }
+
+    /// Check if the machine is gone, or being removed (`Destroying` or
+    /// `Destroyed`).
+    #[inline]
+    pub const fn is_terminal(&self) -> bool {
+        matches!(self, Self::Destroying | Self::Destroyed)
+    }
+
+    /// Check if the machine has exited, and can be started again.
+    #[inline]
+    pub const fn is_stopped(&self) -> bool {
+        matches!(self, Self::Stopped)
+    }
 }
 
 /// The status of the hardware underlying a Fly.io machine.
@@ -370,3 +1039,664 @@ impl CheckStatus {
         matches!(self, Self::Passing)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use reqwest::Method;
+
+    use super::{Client, Machine, MachineState, MachinesQuery, RetryConfig};
+    use crate::{
+        mock::{HttpServer, Response},
+        Error,
+    };
+
+    #[test]
+    fn invalid_origin() {
+        assert!(matches!(
+            Client::try_with_origin("not a url", "token"),
+            Err(Error::Url(url::ParseError::RelativeUrlWithoutBase))
+        ));
+        assert!(matches!(
+            Client::try_with_origin("mailto:ops@example.com", "token"),
+            Err(Error::Url(
+                url::ParseError::RelativeUrlWithCannotBeABaseBase
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn machine_events() {
+        let server = HttpServer::start(vec![Response::new(
+            200,
+            r#"[
+                {"id": "01HF", "type": "start", "status": "started", "source": "user",
+                 "timestamp": 1700000060000, "request": {}},
+                {"id": "01HE", "type": "launch", "status": "created", "source": "user",
+                 "timestamp": 1700000000000}
+            ]"#,
+        )])
+        .await;
+
+        let events = server
+            .client()
+            .machine_events("flytrap", "148e21ea7f3189")
+            .await
+            .unwrap();
+
+        assert_eq!(2, events.len());
+        assert_eq!("start", events[0].kind);
+        assert_eq!("started", events[0].status);
+        assert_eq!("user", events[0].source);
+        assert_eq!(
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            events[1].time()
+        );
+        assert_eq!(
+            vec!["GET /v1/apps/flytrap/machines/148e21ea7f3189/events HTTP/1.1".to_owned()],
+            server.requests()
+        );
+    }
+
+    const MACHINE: &str = r#"{
+        "id": "148e21ea7f3189", "name": "red-river-123", "state": "started", "region": "ord",
+        "instance_id": "01HF8P5FVXAA8K5QWWYJ8JBVBE", "private_ip": "fdaa:0:18:a7b:d6b:0:a:2",
+        "checks": [{"name": "http", "status": "passing", "output": "OK"}]
+    }"#;
+
+    #[tokio::test]
+    async fn machine() {
+        let server = HttpServer::start(vec![
+            Response::new(200, MACHINE),
+            Response::new(404, r#"{"error": "machine not found"}"#),
+            Response::new(500, ""),
+        ])
+        .await;
+        let client = server.client();
+
+        let machine = client.machine("flytrap", "148e21ea7f3189").await.unwrap();
+        assert_eq!("148e21ea7f3189", machine.id);
+        assert_eq!("red-river-123", machine.name);
+        assert!(machine.is_ready());
+
+        match client.machine("flytrap", "0000").await {
+            Err(Error::NotFound { resource }) => {
+                assert_eq!("/v1/apps/flytrap/machines/0000", resource)
+            }
+            other => panic!("expected NotFound, got {other:?}"),
+        }
+
+        assert!(matches!(
+            client.machine("flytrap", "148e21ea7f3189").await,
+            Err(Error::Api(_))
+        ));
+
+        assert_eq!(
+            "GET /v1/apps/flytrap/machines/148e21ea7f3189 HTTP/1.1",
+            server.requests()[0]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "environment")]
+    fn try_default() {
+        use crate::Config;
+
+        let client = Client::from_config(Config::from_vars([("FLY_API_TOKEN", "FlyV1 token")]));
+        assert_eq!("FlyV1 token", client.unwrap().token);
+
+        let client = Client::from_config(Config::from_vars([("FLY_API_TOKEN", "")]));
+        assert_eq!("", client.unwrap().token);
+
+        let client = Client::from_config(Config::from_vars([("FLY_APP_NAME", "flytrap")]));
+        assert!(matches!(client, Err(Error::Unavailable)));
+
+        let config = Config::from_vars([
+            ("FLY_API_TOKEN", "FlyV1 token"),
+            ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+            ("FLYTRAP_API_ORIGIN", "http://localhost:4280"),
+        ]);
+        let client = Client::from_config(config.clone()).unwrap();
+        assert_eq!("http://localhost:4280/", client.origin.as_str());
+        assert_eq!(config.client().unwrap().origin, client.origin);
+
+        let config = Config::from_vars([
+            ("FLY_API_TOKEN", "FlyV1 token"),
+            ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+        ]);
+        let client = Client::from_config(config).unwrap();
+        assert_eq!(
+            Client::PRIVATE_ORIGIN,
+            client.origin.as_str().trim_end_matches('/')
+        );
+    }
+
+    #[test]
+    fn machine_state_predicates() {
+        use MachineState::*;
+
+        // (state, is_terminal, is_stopped)
+        let cases = [
+            (Created, false, false),
+            (Starting, false, false),
+            (Started, false, false),
+            (Stopping, false, false),
+            (Stopped, false, true),
+            (Replacing, false, false),
+            (Destroying, true, false),
+            (Destroyed, true, false),
+            (Unknown, false, false),
+        ];
+
+        for (state, terminal, stopped) in cases {
+            assert_eq!(terminal, state.is_terminal(), "{state:?}");
+            assert_eq!(stopped, state.is_stopped(), "{state:?}");
+        }
+    }
+
+    #[test]
+    fn unknown_machine_state() {
+        let state: MachineState = serde_json::from_str(r#""suspended""#).unwrap();
+        assert_eq!(MachineState::Unknown, state);
+        assert!(!state.is_ready());
+        assert!(!state.is_transition());
+
+        let state: MachineState = serde_json::from_str(r#""stopping""#).unwrap();
+        assert_eq!(MachineState::Stopping, state);
+        assert!(MachineState::Destroyed < MachineState::Unknown);
+
+        let machine: Machine =
+            serde_json::from_str(&MACHINE.replace(r#""started""#, r#""suspended""#)).unwrap();
+        assert_eq!(MachineState::Unknown, machine.state);
+        assert!(!machine.is_running());
+    }
+
+    #[tokio::test]
+    async fn app() {
+        let server = HttpServer::start(vec![
+            Response::new(
+                200,
+                r#"{"id": "5kzm1lq6v2g0ydxp", "name": "flytrap", "status": "deployed",
+                    "organization": {"name": "Flytrap", "slug": "personal"}}"#,
+            ),
+            Response::new(404, r#"{"error": "app not found"}"#),
+        ])
+        .await;
+        let client = server.client();
+
+        let app = client.app("flytrap").await.unwrap();
+        assert_eq!("flytrap", app.name);
+        assert_eq!("deployed", app.status);
+        assert_eq!("personal", app.organization.slug);
+        assert_eq!(None, app.network_name);
+
+        assert!(matches!(
+            client.app("missing").await,
+            Err(Error::NotFound { resource }) if resource == "/v1/apps/missing"
+        ));
+        assert_eq!(
+            vec![
+                "GET /v1/apps/flytrap HTTP/1.1",
+                "GET /v1/apps/missing HTTP/1.1"
+            ],
+            server.requests()
+        );
+    }
+
+    #[tokio::test]
+    async fn volumes() {
+        let server = HttpServer::start(vec![Response::new(
+            200,
+            r#"[
+                {"id": "vol_1zjm0oxpm2q4vyg5", "name": "data", "state": "created", "size_gb": 10,
+                 "region": "ord", "zone": "4ed9", "encrypted": true,
+                 "attached_machine_id": "148e21ea7f3189", "attached_alloc_id": null},
+                {"id": "vol_4d89699c03051800", "name": "data", "state": "created", "size_gb": 1,
+                 "region": "cai", "encrypted": true, "attached_machine_id": null}
+            ]"#,
+        )])
+        .await;
+
+        let volumes = server.client().volumes("flytrap").await.unwrap();
+
+        assert_eq!(2, volumes.len());
+        assert_eq!("vol_1zjm0oxpm2q4vyg5", volumes[0].id);
+        assert_eq!(10, volumes[0].size_gb);
+        assert_eq!("ord", volumes[0].location.to_string());
+        assert_eq!(
+            Some("148e21ea7f3189"),
+            volumes[0].attached_machine_id.as_deref()
+        );
+        assert!(volumes[0].is_attached());
+        assert_eq!("cai", volumes[1].location.to_string());
+        assert!(!volumes[1].is_attached());
+        assert_eq!(
+            vec!["GET /v1/apps/flytrap/volumes HTTP/1.1"],
+            server.requests()
+        );
+    }
+
+    #[tokio::test]
+    async fn not_found() {
+        let server = HttpServer::start(vec![Response::new(404, r#"{"error": "not found"}"#)]).await;
+        let client = server.client();
+
+        let resource = |result: Result<_, Error>| match result {
+            Err(Error::NotFound { resource }) => resource,
+            Err(err) => panic!("expected NotFound, got {err:?}"),
+            Ok(_) => panic!("expected NotFound"),
+        };
+
+        assert_eq!(
+            "/v1/apps/missing/machines",
+            resource(client.machines("missing").await.map(|_| ()))
+        );
+        assert_eq!(
+            "/v1/apps/flytrap/machines/0000/events",
+            resource(client.machine_events("flytrap", "0000").await.map(|_| ()))
+        );
+        assert_eq!(
+            "/v1/apps?org_slug=missing",
+            resource(client.apps("missing").await.map(|_| ()))
+        );
+        assert_eq!(
+            "/v1/apps/missing/ip_assignments",
+            resource(client.ip_assignments("missing").await.map(|_| ()))
+        );
+    }
+
+    #[tokio::test]
+    async fn machine_lifecycle() {
+        let server = HttpServer::start(vec![
+            Response::new(200, r#"{"previous_state": "stopped"}"#),
+            Response::new(200, r#"{"ok": true}"#),
+            Response::new(200, r#"{"ok": true}"#),
+            Response::new(404, r#"{"error": "machine not found"}"#),
+            Response::new(412, r#"{"error": "machine is not stopped"}"#),
+        ])
+        .await;
+        let client = server.client();
+
+        client
+            .start_machine("flytrap", "148e21ea7f3189")
+            .await
+            .unwrap();
+        client
+            .stop_machine("flytrap", "148e21ea7f3189")
+            .await
+            .unwrap();
+        client
+            .restart_machine("flytrap", "148e21ea7f3189")
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            client.start_machine("flytrap", "0000").await,
+            Err(Error::NotFound { .. })
+        ));
+        assert!(matches!(
+            client.start_machine("flytrap", "148e21ea7f3189").await,
+            Err(Error::Api(_))
+        ));
+
+        assert_eq!(
+            vec![
+                "POST /v1/apps/flytrap/machines/148e21ea7f3189/start HTTP/1.1",
+                "POST /v1/apps/flytrap/machines/148e21ea7f3189/stop HTTP/1.1",
+                "POST /v1/apps/flytrap/machines/148e21ea7f3189/restart HTTP/1.1",
+                "POST /v1/apps/flytrap/machines/0000/start HTTP/1.1",
+                "POST /v1/apps/flytrap/machines/148e21ea7f3189/start HTTP/1.1",
+            ],
+            server.requests()
+        );
+    }
+
+    #[tokio::test]
+    async fn wait_for_state() {
+        let machine = |state: &str| MACHINE.replace(r#""started""#, &format!("{state:?}"));
+
+        let server = HttpServer::start(vec![
+            Response::new(400, r#"{"error": "invalid state"}"#),
+            Response::new(200, machine("stopped")),
+            Response::new(200, machine("starting")),
+            Response::new(200, machine("started")),
+        ])
+        .await;
+        let client = server.client();
+
+        let machine = client
+            .wait_for_state(
+                "flytrap",
+                "148e21ea7f3189",
+                MachineState::Started,
+                Duration::from_secs(10),
+            )
+            .await
+            .unwrap();
+        assert_eq!(MachineState::Started, machine.state);
+
+        let requests = server.requests();
+        assert_eq!(4, requests.len());
+        assert!(requests[0].starts_with(
+            "GET /v1/apps/flytrap/machines/148e21ea7f3189/wait?state=started&timeout="
+        ));
+        assert!(requests[1..]
+            .iter()
+            .all(|r| r == "GET /v1/apps/flytrap/machines/148e21ea7f3189 HTTP/1.1"));
+    }
+
+    #[tokio::test]
+    async fn wait_for_state_terminal() {
+        let machine = |state: &str| MACHINE.replace(r#""started""#, &format!("{state:?}"));
+
+        let server = HttpServer::start(vec![
+            Response::new(400, r#"{"error": "invalid state"}"#),
+            Response::new(200, machine("destroying")),
+        ])
+        .await;
+
+        let result = server
+            .client()
+            .wait_for_state(
+                "flytrap",
+                "148e21ea7f3189",
+                MachineState::Started,
+                Duration::from_secs(10),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::State(MachineState::Started))));
+        assert_eq!(2, server.requests().len());
+
+        let server = HttpServer::start(vec![
+            Response::new(400, r#"{"error": "invalid state"}"#),
+            Response::new(200, machine("destroying")),
+            Response::new(200, machine("destroyed")),
+        ])
+        .await;
+
+        let machine = server
+            .client()
+            .wait_for_state(
+                "flytrap",
+                "148e21ea7f3189",
+                MachineState::Destroyed,
+                Duration::from_secs(10),
+            )
+            .await
+            .unwrap();
+        assert_eq!(MachineState::Destroyed, machine.state);
+    }
+
+    #[tokio::test]
+    async fn wait_for_state_unsupported() {
+        let server = HttpServer::start(vec![Response::new(
+            200,
+            MACHINE.replace(r#""started""#, r#""created""#),
+        )])
+        .await;
+        let client = server.client();
+
+        let result = client
+            .wait_for_state(
+                "flytrap",
+                "148e21ea7f3189",
+                MachineState::Unknown,
+                Duration::from_secs(10),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::State(MachineState::Unknown))));
+        assert!(server.requests().is_empty());
+
+        let machine = client
+            .wait_for_state(
+                "flytrap",
+                "148e21ea7f3189",
+                MachineState::Created,
+                Duration::from_secs(10),
+            )
+            .await
+            .unwrap();
+        assert_eq!(MachineState::Created, machine.state);
+        assert_eq!(
+            vec!["GET /v1/apps/flytrap/machines/148e21ea7f3189 HTTP/1.1"],
+            server.requests()
+        );
+    }
+
+    #[tokio::test]
+    async fn wait_for_state_native() {
+        let server = HttpServer::start(vec![
+            Response::new(408, r#"{"error": "deadline_exceeded"}"#),
+            Response::new(200, r#"{"ok": true}"#),
+            Response::new(200, MACHINE),
+        ])
+        .await;
+
+        let machine = server
+            .client()
+            .wait_for_state(
+                "flytrap",
+                "148e21ea7f3189",
+                MachineState::Started,
+                Duration::from_secs(90),
+            )
+            .await
+            .unwrap();
+        assert_eq!(MachineState::Started, machine.state);
+        assert!(server.requests()[0].contains("state=started&timeout=60 "));
+    }
+
+    #[tokio::test]
+    async fn wait_for_state_timeout() {
+        let server = HttpServer::start(vec![Response::new(
+            200,
+            MACHINE.replace("started", "stopped"),
+        )])
+        .await;
+
+        assert!(matches!(
+            server
+                .client()
+                .wait_for_state(
+                    "flytrap",
+                    "148e21ea7f3189",
+                    MachineState::Starting,
+                    Duration::from_millis(600),
+                )
+                .await,
+            Err(Error::Timeout)
+        ));
+        assert!(server.requests().iter().all(|r| !r.contains("/wait")));
+    }
+
+    #[tokio::test]
+    async fn machines_query() {
+        let server = HttpServer::start(vec![Response::new(200, format!("[{MACHINE}]"))]).await;
+        let client = server.client();
+
+        let machines = client.machines("flytrap").await.unwrap();
+        assert_eq!(1, machines.len());
+
+        let query = MachinesQuery::new()
+            .include_deleted(true)
+            .region("ord".parse::<crate::Location>().unwrap())
+            .metadata("fly_process_group", "worker")
+            .metadata("team", "a b");
+        client.machines_with("flytrap", &query).await.unwrap();
+
+        assert_eq!(
+            vec![
+                "GET /v1/apps/flytrap/machines HTTP/1.1",
+                "GET /v1/apps/flytrap/machines?include_deleted=true&region=ord\
+                 &metadata.fly_process_group=worker&metadata.team=a+b HTTP/1.1",
+            ],
+            server.requests()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn machine_timestamps() {
+        use chrono::{TimeZone, Utc};
+
+        use super::Machine;
+
+        let machine: Machine = serde_json::from_str(
+            r#"{
+                "id": "148e21ea7f3189",
+                "name": "red-river-123",
+                "state": "started",
+                "region": "ord",
+                "instance_id": "01HF8P5FVXAA8K5QWWYJ8JBVBE",
+                "private_ip": "fdaa:0:18:a7b:d6b:0:a:2",
+                "config": {"image": "registry.fly.io/flytrap:deployment-01HF8P5F"},
+                "image_ref": {"registry": "registry.fly.io", "repository": "flytrap"},
+                "created_at": "2023-11-16T03:42:15Z",
+                "updated_at": "2023-11-16T03:42:20.512Z",
+                "checks": [{"name": "http", "status": "passing", "output": "OK",
+                            "updated_at": "2023-11-16T03:43:00Z"}],
+                "host_status": "ok"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(Utc.with_ymd_and_hms(2023, 11, 16, 3, 42, 15).unwrap()),
+            machine.created_at
+        );
+        assert_eq!(
+            Some(
+                Utc.with_ymd_and_hms(2023, 11, 16, 3, 42, 20).unwrap()
+                    + chrono::Duration::milliseconds(512)
+            ),
+            machine.updated_at
+        );
+
+        let machine: Machine = serde_json::from_str(MACHINE).unwrap();
+        assert_eq!(None, machine.created_at);
+    }
+
+    #[tokio::test]
+    async fn apps_pages() {
+        let server = HttpServer::start(vec![
+            Response::new(
+                200,
+                r#"{"total_apps": 3, "next_cursor": "c2", "apps": [
+                    {"id": "a1", "name": "flytrap", "machine_count": 2, "network": "default"},
+                    {"id": "a2", "name": "demo", "machine_count": 1, "network": "default"}
+                ]}"#,
+            ),
+            Response::new(
+                200,
+                r#"{"total_apps": 3, "apps": [
+                    {"id": "a3", "name": "worker", "machine_count": 0, "network": "default"}
+                ]}"#,
+            ),
+        ])
+        .await;
+
+        let apps = server.client().apps_all("personal").await.unwrap();
+        assert_eq!(3, apps.total);
+        assert_eq!(None, apps.next_cursor);
+        assert_eq!(
+            vec!["flytrap", "demo", "worker"],
+            apps.iter().map(|app| app.name.as_str()).collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            vec![
+                "GET /v1/apps?org_slug=personal HTTP/1.1",
+                "GET /v1/apps?org_slug=personal&cursor=c2 HTTP/1.1",
+            ],
+            server.requests()
+        );
+    }
+
+    #[tokio::test]
+    async fn retry() {
+        let server = HttpServer::start(vec![
+            Response::new(429, "").header("Retry-After", "0"),
+            Response::new(200, MACHINE),
+            Response::new(503, ""),
+            Response::new(503, ""),
+            Response::new(503, ""),
+            Response::new(429, ""),
+        ])
+        .await;
+        let client = server.client().with_retry(RetryConfig {
+            initial_backoff: Duration::from_millis(10),
+            ..RetryConfig::default()
+        });
+
+        let machine = client.machine("flytrap", "148e21ea7f3189").await.unwrap();
+        assert_eq!("148e21ea7f3189", machine.id);
+        assert_eq!(2, server.requests().len());
+
+        assert!(matches!(
+            client.machine("flytrap", "148e21ea7f3189").await,
+            Err(Error::Api(_))
+        ));
+        assert_eq!(5, server.requests().len());
+
+        // POST requests are not retried by default
+        assert!(client
+            .start_machine("flytrap", "148e21ea7f3189")
+            .await
+            .is_err());
+        assert_eq!(6, server.requests().len());
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let server = HttpServer::start(vec![
+            Response::new(200, MACHINE).delayed(Duration::from_millis(500)),
+            Response::new(200, MACHINE),
+        ])
+        .await;
+        let client = server.client().with_timeout(Duration::from_millis(100));
+
+        assert!(matches!(
+            client.machine("flytrap", "148e21ea7f3189").await,
+            Err(Error::Timeout)
+        ));
+        assert!(server
+            .client()
+            .machine("flytrap", "148e21ea7f3189")
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn retry_delay() {
+        let retry = RetryConfig {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            ..RetryConfig::default()
+        };
+
+        assert_eq!(Duration::from_millis(100), retry.delay(1, None));
+        assert_eq!(Duration::from_millis(200), retry.delay(2, None));
+        assert_eq!(Duration::from_millis(800), retry.delay(4, None));
+        assert_eq!(Duration::from_secs(1), retry.delay(5, None));
+        assert_eq!(Duration::from_secs(1), retry.delay(40, None));
+
+        let retry_after = Some(Duration::from_millis(300));
+        assert_eq!(Duration::from_millis(300), retry.delay(1, retry_after));
+        assert_eq!(
+            Duration::from_secs(1),
+            retry.delay(1, Some(Duration::from_secs(60)))
+        );
+    }
+
+    #[test]
+    fn invalid_request_url() {
+        let client = Client::try_with_origin(Client::PUBLIC_ORIGIN, "token").unwrap();
+
+        assert!(client.request(Method::GET, "/v1/apps").is_ok());
+        assert!(matches!(
+            client.request(Method::GET, "http://[fdaa::3/v1/apps"),
+            Err(Error::Url(_))
+        ));
+    }
+}
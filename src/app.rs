@@ -1,33 +1,62 @@
-use std::net::Ipv6Addr;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::Ipv6Addr,
+};
 
-use futures::future::join_all;
+use futures::{
+    future,
+    stream::{self, Stream, StreamExt, TryStreamExt},
+};
 use hickory_resolver::{
-    error::ResolveError, proto::rr::rdata::AAAA, IntoName, Name, TokioAsyncResolver,
+    error::{ResolveError, ResolveErrorKind},
+    lookup::{Ipv6Lookup, SrvLookup},
+    proto::rr::{rdata::AAAA, RecordType},
+    IntoName, Name,
 };
 
-use crate::{
-    error::Error,
-    resolver::{lookup_txt, Resolver},
-    Node, Peer, Region,
-};
+use crate::{error::Error, resolver::Resolver, Location, Node, Peer, Region};
 
 /// Query the Fly.io [internal DNS][] records for a particular app.
 ///
 /// [internal DNS]: https://fly.io/docs/reference/private-networking/#fly-internal-addresses
 pub struct AppResolver<'r> {
     domain: Name,
-    resolver: &'r TokioAsyncResolver,
+    resolver: &'r Resolver,
+    concurrency: usize,
 }
 
 impl<'r> AppResolver<'r> {
+    /// The maximum number of `AAAA` lookups [`peers`][Self::peers] and
+    /// [`peers_stream`][Self::peers_stream] will have in flight at once, unless
+    /// [overridden][Self::with_concurrency].
+    pub const DEFAULT_CONCURRENCY: usize = 16;
+
     pub(crate) fn new(app: impl Into<String>, resolver: &'r Resolver) -> Self {
         let app: String = app.into();
         let name = Name::from_ascii(app).expect("invalid app name");
-        let domain = name.append_label("internal").unwrap();
+        let domain = name
+            .append_domain(&resolver.domain)
+            .expect("invalid app domain");
 
         Self {
             domain,
-            resolver: &resolver.0,
+            resolver,
+            concurrency: Self::DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Limit the number of `AAAA` lookups [`peers`][Self::peers] and
+    /// [`peers_stream`][Self::peers_stream] will have in flight at once to `n`
+    /// (by default, [16][Self::DEFAULT_CONCURRENCY]).
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    pub fn with_concurrency(self, n: usize) -> Self {
+        assert!(n > 0, "AppResolver concurrency must be at least 1");
+        Self {
+            concurrency: n,
+            ..self
         }
     }
 
@@ -54,32 +83,151 @@ impl<'r> AppResolver<'r> {
     /// resolve all their instance ID’s to private IP addresses.
     pub async fn peers(&self) -> Result<Vec<Peer>, Error> {
         let nodes = self.nodes().await?;
+        self.resolve(nodes).await
+    }
 
-        let addrs = join_all(nodes.iter().map(|node| {
-            let name = Name::from_ascii(&node.id)
-                .expect("invalid node ID")
-                .append_label("vm")
-                .unwrap()
-                .append_domain(&self.domain)
-                .expect("invalid query");
-
-            self.resolver.ipv6_lookup(name)
-        }))
-        .await
-        .into_iter()
-        .collect::<Result<Vec<_>, ResolveError>>()
-        .map_err(Error::from)?;
-
-        Ok(nodes
-            .into_iter()
-            .zip(addrs.into_iter())
-            .filter_map(|(node, addrs)| {
-                if let Some(AAAA(addr)) = addrs.into_iter().next() {
-                    Some(node.into_peer(addr))
-                } else {
-                    None
-                }
+    /// Count the running [instances][AppResolver::nodes] of this Fly.io app,
+    /// without resolving their addresses.
+    ///
+    /// Unlike [`nodes`][AppResolver::nodes], empty or malformed entries in the
+    /// `vms` record are skipped rather than failing the lookup.
+    pub async fn count(&self) -> Result<usize, Error> {
+        Ok(self.valid_nodes().await?.count())
+    }
+
+    /// Count the distinct regions with a running instance of this Fly.io app;
+    /// see [`count`][AppResolver::count].
+    pub async fn regions_count(&self) -> Result<usize, Error> {
+        let locations: BTreeSet<Location> = self
+            .valid_nodes()
+            .await?
+            .map(|node| node.location)
+            .collect();
+
+        Ok(locations.len())
+    }
+
+    /// Count the running [instances][AppResolver::nodes] of this Fly.io app in
+    /// each region where it's deployed.
+    ///
+    /// The map is ordered geographically, like [`Location`] (or by region code,
+    /// without the _regions_ feature).
+    pub async fn region_counts(&self) -> Result<BTreeMap<Location, usize>, Error> {
+        let mut counts = BTreeMap::new();
+
+        for node in self.nodes().await? {
+            *counts.entry(node.location).or_insert(0) += 1;
+        }
+
+        Ok(counts)
+    }
+
+    /// Find the running [instances][AppResolver::nodes] of this Fly.io app in
+    /// the given region.
+    ///
+    /// ```no_run
+    /// use flytrap::{Region, Resolver};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), flytrap::Error> {
+    /// let resolver = Resolver::new()?;
+    /// let local = resolver.app("flytrap").nodes_in_region(Region::Seattle).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn nodes_in_region(&self, region: impl Into<Location>) -> Result<Vec<Node>, Error> {
+        let location = region.into();
+        let mut nodes = self.nodes().await?;
+        nodes.retain(|node| node.location == location);
+
+        Ok(nodes)
+    }
+
+    /// Find the running [instances][AppResolver::nodes] of this Fly.io app in
+    /// the given region, and resolve their instance ID’s to private IP
+    /// addresses.
+    ///
+    /// Only the nodes in `region` are looked up.
+    pub async fn peers_in_region(&self, region: impl Into<Location>) -> Result<Vec<Peer>, Error> {
+        let nodes = self.nodes_in_region(region).await?;
+        self.resolve(nodes).await
+    }
+
+    /// Find all running [instances][AppResolver::nodes] of this Fly.io app,
+    /// and yield each one as a [`Peer`] as soon as its private IP address has
+    /// been resolved.
+    ///
+    /// Unlike [`peers`][AppResolver::peers], which waits for every lookup to
+    /// finish, peers are produced in the order their lookups complete, and a
+    /// failed lookup only fails that item of the stream.
+    pub fn peers_stream(&self) -> impl Stream<Item = Result<Peer, Error>> + '_ {
+        stream::once(self.nodes())
+            .map(|nodes| match nodes {
+                Ok(nodes) => stream::iter(nodes).map(Ok).left_stream(),
+                Err(err) => stream::once(future::ready(Err(err))).right_stream(),
             })
+            .flatten()
+            .map(move |node| async move {
+                let node = node?;
+                let addrs = self.ipv6_lookup(self.node_name(&node)).await?;
+
+                Ok(addrs
+                    .into_iter()
+                    .next()
+                    .map(|AAAA(addr)| node.into_peer(addr)))
+            })
+            .buffer_unordered(self.concurrency)
+            .filter_map(|result: Result<Option<Peer>, Error>| future::ready(result.transpose()))
+    }
+
+    /// Find the instances of this Fly.io app which offer the named TCP
+    /// `service`, and the port each one offers it on, by querying the
+    /// `_<service>._tcp.<app>.internal` `SRV` record.
+    ///
+    /// Fly.io does not currently publish `SRV` records in its internal DNS, so
+    /// this is only useful with DNS servers which do. If the `SRV` record does
+    /// not exist, an `Unavailable` [error][Error] is returned; if `service`
+    /// can't form a valid DNS name, a `Resolve` error is returned.
+    ///
+    /// Like [`peers`][Self::peers], the instances' addresses are looked up
+    /// concurrently, up to the [concurrency limit][Self::with_concurrency].
+    pub async fn services(&self, service: &str) -> Result<Vec<(Peer, u16)>, Error> {
+        let query = Name::from_ascii(format!("_{service}._tcp"))
+            .and_then(|name| name.append_domain(&self.domain))
+            .map_err(ResolveError::from)?;
+
+        let records = match self.resolver.lookup(query, RecordType::SRV).await {
+            Ok(records) => SrvLookup::from(records),
+            Err(err) if matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. }) => {
+                return Err(Error::Unavailable)
+            }
+            Err(err) => return Err(Error::from(err)),
+        };
+
+        let nodes = self.nodes().await?;
+        let mut targets = Vec::new();
+        let mut ports = Vec::new();
+
+        for record in records.iter() {
+            let target = record.target();
+            let id = match target.iter().next() {
+                Some(label) => String::from_utf8_lossy(label),
+                None => continue,
+            };
+            let Some(node) = nodes.iter().find(|node| node.id == id) else {
+                continue;
+            };
+
+            targets.push((node.clone(), target.clone()));
+            ports.push(record.port());
+        }
+
+        let peers = self.resolve_targets(targets).await?;
+
+        Ok(peers
+            .into_iter()
+            .zip(ports)
+            .filter_map(|(peer, port)| Some((peer?, port)))
             .collect())
     }
 
@@ -94,11 +242,60 @@ impl<'r> AppResolver<'r> {
             .append_domain(&self.domain)
             .expect("invalid query");
 
-        let results = self.resolver.ipv6_lookup(top).await.map_err(Error::from)?;
+        let results = self.ipv6_lookup(top).await.map_err(Error::from)?;
 
         Ok(results.into_iter().map(|r| r.0).collect())
     }
 
+    /// Find the geographically-nearest _n_ instances of this Fly.io app, as
+    /// [`Peer`]s.
+    ///
+    /// The [nearest addresses][Self::nearest_peer_addresses] are matched to the
+    /// app's [peers][Self::peers_stream], whose addresses are looked up only
+    /// until every nearest address has been matched. An address which doesn't
+    /// match any peer (e.g., because the instance started between the queries)
+    /// is returned with an empty ID and an [unknown][crate::RegionCode::UNKNOWN]
+    /// location (or an empty string, without the _regions_ feature).
+    pub async fn nearest_peers(&self, n: usize) -> Result<Vec<Peer>, Error> {
+        let addrs = self.nearest_peer_addresses(n).await?;
+
+        let mut unmatched: BTreeSet<Ipv6Addr> = addrs.iter().copied().collect();
+        let mut matched: BTreeMap<Ipv6Addr, Peer> = BTreeMap::new();
+
+        let mut peers = Box::pin(self.peers_stream());
+        while !unmatched.is_empty() {
+            let Some(peer) = peers.try_next().await? else {
+                break;
+            };
+
+            if unmatched.remove(&peer.private_ip) {
+                matched.insert(peer.private_ip, peer);
+            }
+        }
+
+        Ok(addrs
+            .into_iter()
+            .map(|addr| match matched.get(&addr) {
+                Some(peer) => peer.clone(),
+                None => Peer::new(unknown_location(), "", addr),
+            })
+            .collect())
+    }
+
+    /// Find the running instance of this Fly.io app with the given private IP
+    /// address (e.g., the source of an inbound connection).
+    ///
+    /// All the app's [peers][Self::peers] are resolved to find a match; `None`
+    /// is returned if no instance has the address.
+    pub async fn node_for_ip(&self, ip: Ipv6Addr) -> Result<Option<Node>, Error> {
+        let peers = self.peers().await?;
+
+        Ok(peers
+            .into_iter()
+            .find(|peer| peer.private_ip == ip)
+            .map(|peer| peer.node))
+    }
+
     /// Perform an arbitrary `TXT` record query on the `<app>.internal` domain.
     pub async fn txt(&self, name: impl IntoName) -> Result<String, Error> {
         let query = name
@@ -107,6 +304,465 @@ impl<'r> AppResolver<'r> {
             .append_domain(&self.domain)
             .expect("invalid app domain");
 
-        lookup_txt(self.resolver, query).await
+        self.resolver.lookup_txt(query).await
+    }
+
+    async fn valid_nodes(&self) -> Result<impl Iterator<Item = Node>, Error> {
+        let value = self.txt("vms").await?;
+        let nodes: Vec<Node> = value
+            .split(',')
+            .filter_map(|peer| peer.trim().parse::<Node>().ok())
+            .collect();
+
+        Ok(nodes.into_iter())
+    }
+
+    async fn ipv6_lookup(&self, name: Name) -> Result<Ipv6Lookup, ResolveError> {
+        self.resolver
+            .lookup(name, RecordType::AAAA)
+            .await
+            .map(Ipv6Lookup::from)
+    }
+
+    /// Resolve the private IP addresses of `nodes`, skipping any which have
+    /// none.
+    async fn resolve(&self, nodes: Vec<Node>) -> Result<Vec<Peer>, Error> {
+        let targets = nodes
+            .into_iter()
+            .map(|node| {
+                let name = self.node_name(&node);
+                (node, name)
+            })
+            .collect();
+
+        let peers = self.resolve_targets(targets).await?;
+        Ok(peers.into_iter().flatten().collect())
+    }
+
+    /// Look up the address of each node at the paired name, at most
+    /// `concurrency` at a time; `None` for a node whose name has no address.
+    async fn resolve_targets(
+        &self,
+        targets: Vec<(Node, Name)>,
+    ) -> Result<Vec<Option<Peer>>, Error> {
+        let names: Vec<Name> = targets.iter().map(|(_, name)| name.clone()).collect();
+
+        let addrs = stream::iter(names)
+            .map(|name| self.ipv6_lookup(name))
+            .buffered(self.concurrency)
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(Error::from)?;
+
+        Ok(targets
+            .into_iter()
+            .zip(addrs)
+            .map(|((node, _), addrs)| {
+                addrs
+                    .into_iter()
+                    .next()
+                    .map(|AAAA(addr)| node.into_peer(addr))
+            })
+            .collect())
+    }
+
+    /// The `<id>.vm.<app>.internal` name of a [`Node`].
+    fn node_name(&self, node: &Node) -> Name {
+        Name::from_ascii(&node.id)
+            .expect("invalid node ID")
+            .append_label("vm")
+            .unwrap()
+            .append_domain(&self.domain)
+            .expect("invalid query")
+    }
+}
+
+#[cfg(feature = "regions")]
+fn unknown_location() -> Location {
+    Location::Unknown(crate::RegionCode::UNKNOWN)
+}
+
+#[cfg(not(feature = "regions"))]
+fn unknown_location() -> Location {
+    Location::new()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures::TryStreamExt;
+    use hickory_resolver::proto::rr::Record;
+
+    use crate::{
+        mock::{aaaa, srv, txt, DnsServer},
+        Error, Location,
+    };
+
+    #[tokio::test]
+    async fn peers_stream() {
+        let server = DnsServer::start(vec![
+            txt(
+                "vms.flytrap.internal",
+                "148e21dad76789 sea,4d89699c030518 ams,6e82de14c35038 sin",
+            ),
+            aaaa(
+                "148e21dad76789.vm.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:2".parse().unwrap(),
+            ),
+            aaaa(
+                "4d89699c030518.vm.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:3".parse().unwrap(),
+            ),
+            aaaa(
+                "6e82de14c35038.vm.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:4".parse().unwrap(),
+            ),
+        ])
+        .await;
+        let resolver = server.resolver();
+        let app = resolver.app("flytrap");
+
+        let mut streamed: Vec<_> = app.peers_stream().try_collect().await.unwrap();
+        let mut peers = app.peers().await.unwrap();
+        streamed.sort();
+        peers.sort();
+
+        assert_eq!(3, streamed.len());
+        assert_eq!(peers, streamed);
+        assert!(streamed
+            .iter()
+            .zip(peers.iter())
+            .all(|(a, b)| a.private_ip == b.private_ip));
+    }
+
+    #[tokio::test]
+    async fn concurrency() {
+        let ids: Vec<String> = (0..24).map(|i| format!("{i:014x}")).collect();
+        let mut records: Vec<Record> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                aaaa(
+                    &format!("{id}.vm.flytrap.internal"),
+                    format!("fdaa:0:18:a7b:d6b:0:a:{i:x}").parse().unwrap(),
+                )
+            })
+            .collect();
+        let vms: Vec<String> = ids.iter().map(|id| format!("{id} sea")).collect();
+        records.push(txt("vms.flytrap.internal", &vms.join(",")));
+
+        let server = DnsServer::start_delayed(records, Duration::from_millis(20)).await;
+        let resolver = server.resolver();
+
+        let peers = resolver
+            .app("flytrap")
+            .with_concurrency(4)
+            .peers()
+            .await
+            .unwrap();
+
+        assert_eq!(24, peers.len());
+        assert!(peers.iter().zip(&ids).all(|(peer, id)| &peer.id == id));
+        assert!(server.peak_concurrency() <= 4);
+
+        resolver.app("flytrap").peers().await.unwrap();
+        assert!(server.peak_concurrency() > 4);
+    }
+
+    #[tokio::test]
+    async fn nearest_peers() {
+        let server = DnsServer::start(vec![
+            txt(
+                "vms.flytrap.internal",
+                "148e21dad76789 sea,4d89699c030518 ams",
+            ),
+            aaaa(
+                "148e21dad76789.vm.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:2".parse().unwrap(),
+            ),
+            aaaa(
+                "4d89699c030518.vm.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:3".parse().unwrap(),
+            ),
+            aaaa(
+                "top2.nearest.of.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:3".parse().unwrap(),
+            ),
+            aaaa(
+                "top2.nearest.of.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:9".parse().unwrap(),
+            ),
+        ])
+        .await;
+        let resolver = server.resolver();
+
+        let peers = resolver.app("flytrap").nearest_peers(2).await.unwrap();
+
+        assert_eq!(2, peers.len());
+        assert_eq!("4d89699c030518", peers[0].id);
+        assert_eq!("ams", peers[0].location.to_string());
+        assert_eq!("", peers[1].id);
+        assert_eq!(
+            "fdaa:0:18:a7b:d6b:0:a:9"
+                .parse::<std::net::Ipv6Addr>()
+                .unwrap(),
+            peers[1].private_ip
+        );
+    }
+
+    #[tokio::test]
+    async fn nearest_peers_lookups() {
+        let ids: Vec<String> = (0..24).map(|i| format!("{i:014x}")).collect();
+        let mut records: Vec<Record> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                aaaa(
+                    &format!("{id}.vm.flytrap.internal"),
+                    format!("fdaa:0:18:a7b:d6b:0:a:{i:x}").parse().unwrap(),
+                )
+            })
+            .collect();
+        let vms: Vec<String> = ids.iter().map(|id| format!("{id} sea")).collect();
+        records.push(txt("vms.flytrap.internal", &vms.join(",")));
+        records.push(aaaa(
+            "top2.nearest.of.flytrap.internal",
+            "fdaa:0:18:a7b:d6b:0:a:1".parse().unwrap(),
+        ));
+        records.push(aaaa(
+            "top2.nearest.of.flytrap.internal",
+            "fdaa:0:18:a7b:d6b:0:a:0".parse().unwrap(),
+        ));
+
+        let server = DnsServer::start(records).await;
+        let resolver = server.resolver();
+
+        let peers = resolver
+            .app("flytrap")
+            .with_concurrency(2)
+            .nearest_peers(2)
+            .await
+            .unwrap();
+
+        assert_eq!(ids[1], peers[0].id);
+        assert_eq!(ids[0], peers[1].id);
+
+        // The nearest and vms queries, then a few AAAA lookups; not one per VM.
+        let queries = server.queries();
+        assert!(queries.len() <= 6, "{queries:?}");
+    }
+
+    #[tokio::test]
+    async fn in_region() {
+        let server = DnsServer::start(vec![
+            txt(
+                "vms.flytrap.internal",
+                "148e21dad76789 sea,4d89699c030518 ams,6e82de14c35038 sea",
+            ),
+            aaaa(
+                "148e21dad76789.vm.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:2".parse().unwrap(),
+            ),
+            aaaa(
+                "6e82de14c35038.vm.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:4".parse().unwrap(),
+            ),
+        ])
+        .await;
+        let resolver = server.resolver();
+        let app = resolver.app("flytrap");
+        let sea = || "sea".parse::<Location>().unwrap();
+
+        let nodes = app.nodes_in_region(sea()).await.unwrap();
+        let ids: Vec<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(vec!["148e21dad76789", "6e82de14c35038"], ids);
+
+        let peers = app.peers_in_region(sea()).await.unwrap();
+        assert_eq!(2, peers.len());
+        assert!(!server
+            .queries()
+            .iter()
+            .any(|query| query.starts_with("4d89699c030518")));
+
+        let nrt: Location = "nrt".parse().unwrap();
+        assert!(app.nodes_in_region(nrt).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn domain() {
+        let server =
+            DnsServer::start(vec![txt("vms.flytrap.test.local", "148e21dad76789 sea")]).await;
+        let resolver = server.resolver().with_domain("test.local").unwrap();
+
+        let nodes = resolver.app("flytrap").nodes().await.unwrap();
+
+        assert_eq!(1, nodes.len());
+        assert_eq!(vec!["vms.flytrap.test.local. TXT"], server.queries());
+
+        let label = "x".repeat(64);
+        assert!(matches!(
+            server.resolver().with_domain(&label),
+            Err(Error::Resolve(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn count() {
+        let server = DnsServer::start(vec![txt(
+            "vms.flytrap.internal",
+            "148e21dad76789 ord,,4d89699c030518 ams, 6e82de14c35038 ord ,garbage, ",
+        )])
+        .await;
+        let resolver = server.resolver();
+        let app = resolver.app("flytrap");
+
+        assert_eq!(3, app.count().await.unwrap());
+        assert_eq!(2, app.regions_count().await.unwrap());
+        assert!(server.queries().iter().all(|query| query.ends_with(" TXT")));
+    }
+
+    #[tokio::test]
+    async fn node_for_ip() {
+        let server = DnsServer::start(vec![
+            txt(
+                "vms.flytrap.internal",
+                "148e21dad76789 sea,4d89699c030518 ams",
+            ),
+            aaaa(
+                "148e21dad76789.vm.flytrap.internal",
+                "fdaa:0:1:a7b:1c4::2".parse().unwrap(),
+            ),
+            aaaa(
+                "4d89699c030518.vm.flytrap.internal",
+                "fdaa:0:1:a7b:1c5::3".parse().unwrap(),
+            ),
+        ])
+        .await;
+        let resolver = server.resolver();
+        let app = resolver.app("flytrap");
+
+        let node = app
+            .node_for_ip("fdaa:0:1:a7b:1c5::3".parse().unwrap())
+            .await
+            .unwrap()
+            .expect("no matching node");
+        assert_eq!("4d89699c030518", node.id);
+        assert_eq!("ams", node.location.to_string());
+
+        let missing = app
+            .node_for_ip("fdaa:0:1:a7b:1c6::4".parse().unwrap())
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn region_counts() {
+        let server = DnsServer::start(vec![txt(
+            "vms.flytrap.internal",
+            "148e21dad76789 ord,4d89699c030518 ams,6e82de14c35038 ord,d8d9e3d0a10048 ams,1781973f6e2389 ord",
+        )])
+        .await;
+        let resolver = server.resolver();
+
+        let counts = resolver.app("flytrap").region_counts().await.unwrap();
+        let counts: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(location, count)| (location.to_string(), count))
+            .collect();
+
+        #[cfg(feature = "regions")]
+        assert_eq!(vec![("ord".to_owned(), 3), ("ams".to_owned(), 2)], counts);
+        #[cfg(not(feature = "regions"))]
+        assert_eq!(vec![("ams".to_owned(), 2), ("ord".to_owned(), 3)], counts);
+        assert_eq!(1, server.queries().len());
+    }
+
+    #[tokio::test]
+    async fn cache() {
+        let server = DnsServer::start(vec![
+            txt("vms.flytrap.internal", "148e21dad76789 sea"),
+            aaaa(
+                "148e21dad76789.vm.flytrap.internal",
+                "fdaa:0:18:a7b:d6b:0:a:2".parse().unwrap(),
+            ),
+        ])
+        .await;
+        let resolver = server.resolver().with_cache(Duration::from_secs(60));
+        let app = resolver.app("flytrap");
+
+        let (a, b) = futures::future::join(app.nodes(), app.nodes()).await;
+        assert_eq!(a.unwrap(), b.unwrap());
+        assert_eq!(vec!["vms.flytrap.internal. TXT"], server.queries());
+
+        let peers = app.peers().await.unwrap();
+        assert_eq!(1, peers.len());
+        assert_eq!(2, server.queries().len());
+
+        assert_eq!(peers, app.peers().await.unwrap());
+        assert_eq!(2, server.queries().len());
+
+        let uncached = server.resolver();
+        uncached.app("flytrap").peers().await.unwrap();
+        assert_eq!(4, server.queries().len());
+    }
+
+    #[tokio::test]
+    async fn services() {
+        let server = DnsServer::start_delayed(
+            vec![
+                txt(
+                    "vms.flytrap.internal",
+                    "148e21dad76789 sea,4d89699c030518 ams",
+                ),
+                srv(
+                    "_http._tcp.flytrap.internal",
+                    8080,
+                    "148e21dad76789.vm.flytrap.internal",
+                ),
+                srv(
+                    "_http._tcp.flytrap.internal",
+                    8081,
+                    "4d89699c030518.vm.flytrap.internal",
+                ),
+                aaaa(
+                    "148e21dad76789.vm.flytrap.internal",
+                    "fdaa:0:18:a7b:d6b:0:a:2".parse().unwrap(),
+                ),
+                aaaa(
+                    "4d89699c030518.vm.flytrap.internal",
+                    "fdaa:0:18:a7b:d6b:0:a:3".parse().unwrap(),
+                ),
+            ],
+            Duration::from_millis(50),
+        )
+        .await;
+        let resolver = server.resolver();
+        let app = resolver.app("flytrap");
+
+        let mut services = app.services("http").await.unwrap();
+        assert_eq!(2, server.peak_concurrency());
+        services.sort();
+
+        assert_eq!(2, services.len());
+        assert_eq!("148e21dad76789", services[0].0.id);
+        assert_eq!(8080, services[0].1);
+        assert_eq!(
+            "fdaa:0:18:a7b:d6b:0:a:3"
+                .parse::<std::net::Ipv6Addr>()
+                .unwrap(),
+            services[1].0.private_ip
+        );
+        assert_eq!(8081, services[1].1);
+
+        assert!(matches!(
+            app.services("postgres").await,
+            Err(Error::Unavailable)
+        ));
+        assert!(matches!(
+            app.services(&"x".repeat(64)).await,
+            Err(Error::Resolve(_))
+        ));
     }
 }
@@ -0,0 +1,346 @@
+use std::net::{Ipv6Addr, SocketAddr};
+
+#[cfg(feature = "api")]
+use crate::api::Client;
+use crate::{error::Error, Location, Machine, Placement};
+#[cfg(feature = "dns")]
+use crate::{try_dns_server_address, Resolver};
+
+/// Settings for connecting to Fly.io, read once from `$FLY_` and `$FLYTRAP_`
+/// [environment variables][env-vars].
+///
+/// A `Config` can construct a [`Placement`], [`Resolver`], and
+/// [`api::Client`][Client] which agree with each other, without each reading
+/// the environment independently. Use [`Config::from_vars`] to build one from
+/// an explicit set of variables (e.g., in tests).
+///
+/// Besides the Fly.io [runtime environment][env-vars], two variables are
+/// read to override Flytrap's defaults:
+///
+/// - `$FLYTRAP_DNS_SERVER`: the `address:port` of the internal DNS server
+/// - `$FLYTRAP_API_ORIGIN`: the origin URL for the Machines API
+///
+/// Values which are empty or can't be parsed are treated as unset, except for
+/// `$FLY_API_TOKEN`, which is kept as-is once set.
+///
+/// [env-vars]: https://fly.io/docs/reference/runtime-environment/#environment-variables
+///
+/// ```
+/// use flytrap::Config;
+///
+/// let config = Config::from_vars([
+///     ("FLY_APP_NAME", "flytrap"),
+///     ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+/// ]);
+///
+/// assert_eq!(config.app.as_deref(), Some("flytrap"));
+/// ```
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct Config {
+    /// The Fly.io application name (`$FLY_APP_NAME`).
+    pub app: Option<String>,
+    /// The process group of this machine (`$FLY_PROCESS_GROUP`).
+    pub process_group: Option<String>,
+    /// The public IPv6 address of this machine (`$FLY_PUBLIC_IP`).
+    pub public_ip: Option<Ipv6Addr>,
+    /// The private IPv6 address of this machine (`$FLY_PRIVATE_IP`).
+    pub private_ip: Option<Ipv6Addr>,
+    /// The machine or allocation ID (`$FLY_ALLOC_ID`).
+    pub allocation: Option<String>,
+    /// The Fly.io machine ID (`$FLY_MACHINE_ID`).
+    pub machine_id: Option<String>,
+    /// The Docker image running on this machine (`$FLY_IMAGE_REF`).
+    pub image: Option<String>,
+    /// The version of this machine's configuration (`$FLY_MACHINE_VERSION`).
+    pub machine_version: Option<String>,
+    /// The memory allocated to this machine, in MB (`$FLY_VM_MEMORY_MB`).
+    pub memory: Option<usize>,
+    /// The region code where this machine is running (`$FLY_REGION`).
+    pub region: Option<String>,
+    /// A Machines API token (`$FLY_API_TOKEN`).
+    pub api_token: Option<String>,
+    /// The Machines API origin (`$FLYTRAP_API_ORIGIN`).
+    pub api_origin: Option<String>,
+    /// The internal DNS server address (`$FLYTRAP_DNS_SERVER`).
+    pub dns_server: Option<SocketAddr>,
+}
+
+impl Config {
+    /// The names of the environment variables a [`Config`] reads.
+    const VARS: [&'static str; 13] = [
+        "FLY_APP_NAME",
+        "FLY_PROCESS_GROUP",
+        "FLY_PUBLIC_IP",
+        "FLY_PRIVATE_IP",
+        "FLY_ALLOC_ID",
+        "FLY_MACHINE_ID",
+        "FLY_IMAGE_REF",
+        "FLY_MACHINE_VERSION",
+        "FLY_VM_MEMORY_MB",
+        "FLY_REGION",
+        "FLY_API_TOKEN",
+        "FLYTRAP_API_ORIGIN",
+        "FLYTRAP_DNS_SERVER",
+    ];
+
+    /// Read a [`Config`] from the process environment.
+    #[cfg(feature = "environment")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Read a [`Config`] by calling `lookup` with the name of each variable it
+    /// reads; `lookup` returns `None` for unset variables.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use flytrap::Config;
+    ///
+    /// let vars = HashMap::from([("FLY_APP_NAME", "flytrap")]);
+    /// let config = Config::from_lookup(|name| vars.get(name).map(|v| v.to_string()));
+    ///
+    /// assert_eq!(config.app.as_deref(), Some("flytrap"));
+    /// ```
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        Self::from_vars(
+            Self::VARS
+                .iter()
+                .filter_map(|&name| lookup(name).map(|value| (name, value))),
+        )
+    }
+
+    /// Read a [`Config`] from the given `(name, value)` pairs, as though they
+    /// were environment variables.
+    pub fn from_vars<K, V>(vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let mut config = Self::default();
+
+        for (name, value) in vars {
+            let value: String = value.into();
+            if value.is_empty() && name.as_ref() != "FLY_API_TOKEN" {
+                continue;
+            }
+
+            match name.as_ref() {
+                "FLY_APP_NAME" => config.app = Some(value),
+                "FLY_PROCESS_GROUP" => config.process_group = Some(value),
+                "FLY_PUBLIC_IP" => config.public_ip = value.parse().ok(),
+                "FLY_PRIVATE_IP" => config.private_ip = value.parse().ok(),
+                "FLY_ALLOC_ID" => config.allocation = Some(value),
+                "FLY_MACHINE_ID" => config.machine_id = Some(value),
+                "FLY_IMAGE_REF" => config.image = Some(value),
+                "FLY_MACHINE_VERSION" => config.machine_version = Some(value),
+                "FLY_VM_MEMORY_MB" => config.memory = value.parse().ok(),
+                "FLY_REGION" => config.region = Some(value),
+                "FLY_API_TOKEN" => config.api_token = Some(value),
+                "FLYTRAP_API_ORIGIN" => config.api_origin = Some(value),
+                "FLYTRAP_DNS_SERVER" => config.dns_server = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        config
+    }
+
+    /// Checks if the configuration describes a process running in the Fly.io
+    /// runtime environment (i.e., `$FLY_APP_NAME` and `$FLY_PRIVATE_IP` are
+    /// set).
+    pub fn hosted(&self) -> bool {
+        self.app.is_some() && self.private_ip.is_some()
+    }
+
+    /// Build the [`Placement`] of the current process.
+    ///
+    /// If `$FLY_APP_NAME`, `$FLY_PRIVATE_IP`, `$FLY_ALLOC_ID`, or `$FLY_REGION`
+    /// is unset, an `Unavailable` error is returned.
+    /// If `$FLY_REGION` is not a valid region code, an `Environment` error is
+    /// returned.
+    pub fn placement(&self) -> Result<Placement, Error> {
+        let app = self.app.clone().ok_or(Error::Unavailable)?;
+        let private_ip = self.private_ip.ok_or(Error::Unavailable)?;
+        let allocation = self.allocation.clone().ok_or(Error::Unavailable)?;
+        let region_code = self.region.as_deref().ok_or(Error::Unavailable)?;
+
+        #[cfg(feature = "regions")]
+        let location: Location = region_code.parse().map_err(|_| Error::Environment {
+            name: "FLY_REGION",
+            value: region_code.to_owned(),
+        })?;
+        #[cfg(not(feature = "regions"))]
+        let location: Location = region_code.to_owned();
+
+        Ok(Placement {
+            app,
+            process_group: self.process_group.clone(),
+            public_ip: self.public_ip,
+            private_ip,
+            allocation,
+            machine: self.machine(),
+            location,
+        })
+    }
+
+    /// Build the [`Machine`] details of the current process, if
+    /// `$FLY_MACHINE_ID` and `$FLY_MACHINE_VERSION` are set.
+    pub fn machine(&self) -> Option<Machine> {
+        Some(Machine {
+            id: self.machine_id.clone()?,
+            image: self.image.clone(),
+            version: self.machine_version.clone()?,
+            memory: self.memory,
+        })
+    }
+
+    /// Build a [`Resolver`] which queries the configured DNS server, or the
+    /// Fly.io DNS server for the configured private IP address.
+    ///
+    /// If neither is known, an `Unavailable` error is returned; if the private
+    /// IP address is not a Fly.io private networking address, an `Address`
+    /// error is returned.
+    #[cfg(feature = "dns")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dns")))]
+    pub fn resolver(&self) -> Result<Resolver, Error> {
+        self.resolver_from(self.private_ip, self.hosted())
+    }
+
+    /// Build a [`Resolver`] like [`resolver`][Self::resolver] does, but for
+    /// the given `local` private IP address and `hosted` status.
+    #[cfg(feature = "dns")]
+    pub(crate) fn resolver_from(
+        &self,
+        local: Option<Ipv6Addr>,
+        hosted: bool,
+    ) -> Result<Resolver, Error> {
+        use std::net::IpAddr;
+
+        if let Some(server) = self.dns_server {
+            return Ok(Resolver::with_source(server, None));
+        }
+
+        let local = local.ok_or(Error::Unavailable)?;
+        let server = try_dns_server_address(local, hosted)?;
+
+        Ok(Resolver::with_source(
+            SocketAddr::new(IpAddr::V6(server), 53),
+            Some(SocketAddr::new(IpAddr::V6(local), 0)),
+        ))
+    }
+
+    /// Build an [`api::Client`][Client] using the configured API token.
+    ///
+    /// Requests are sent to `$FLYTRAP_API_ORIGIN` if set; otherwise to the
+    /// [private][Client::PRIVATE_ORIGIN] API origin if a private IP address is
+    /// known, or the [public][Client::PUBLIC_ORIGIN] origin if not.
+    ///
+    /// If `$FLY_API_TOKEN` is unset, an `Unavailable` error is returned.
+    #[cfg(feature = "api")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api")))]
+    pub fn client(&self) -> Result<Client, Error> {
+        let token = self.api_token.clone().ok_or(Error::Unavailable)?;
+        let origin = match (&self.api_origin, self.private_ip) {
+            (Some(origin), _) => origin.as_str(),
+            (None, Some(_)) => Client::PRIVATE_ORIGIN,
+            (None, None) => Client::PUBLIC_ORIGIN,
+        };
+
+        Client::try_with_origin(origin, token)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Config;
+    use crate::Error;
+
+    const VARS: [(&str, &str); 13] = [
+        ("FLY_APP_NAME", "flytrap"),
+        ("FLY_PROCESS_GROUP", "app"),
+        ("FLY_PUBLIC_IP", "2605:4c40:95:dfa1:0:dc2c:88b9:1"),
+        ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+        ("FLY_ALLOC_ID", "148e21dad76789"),
+        ("FLY_MACHINE_ID", "148e21dad76789"),
+        (
+            "FLY_IMAGE_REF",
+            "registry.fly.io/flytrap:deployment-01HF8P5F",
+        ),
+        ("FLY_MACHINE_VERSION", "01HF8P5FVXAA8K5QWWYJ8JBVBE"),
+        ("FLY_VM_MEMORY_MB", "256"),
+        ("FLY_REGION", "sea"),
+        ("FLY_API_TOKEN", "FlyV1 token"),
+        ("FLYTRAP_API_ORIGIN", "http://localhost:4280"),
+        ("HOME", "/root"),
+    ];
+
+    #[test]
+    fn from_vars() {
+        let config = Config::from_vars(VARS);
+
+        assert!(config.hosted());
+        assert_eq!(Some("flytrap"), config.app.as_deref());
+        assert_eq!(Some(256), config.memory);
+        assert_eq!(None, config.dns_server);
+
+        let placement = config.placement().unwrap();
+        assert_eq!("flytrap", placement.app);
+        assert_eq!(Some("app"), placement.process_group.as_deref());
+        assert_eq!(config.private_ip, Some(placement.private_ip));
+        assert_eq!(config.public_ip, placement.public_ip);
+        assert_eq!("sea", placement.location.to_string());
+        assert_eq!(config.machine(), placement.machine);
+        assert_eq!(
+            Some("01HF8P5FVXAA8K5QWWYJ8JBVBE"),
+            placement.machine.as_ref().map(|m| m.version.as_str())
+        );
+
+        #[cfg(feature = "dns")]
+        assert!(config.resolver().is_ok());
+        #[cfg(feature = "api")]
+        assert!(config.client().is_ok());
+    }
+
+    #[test]
+    fn missing_vars() {
+        let config = Config::from_vars([("FLY_APP_NAME", "flytrap"), ("FLY_PRIVATE_IP", "")]);
+
+        assert!(!config.hosted());
+        assert_eq!(None, config.machine());
+        assert!(matches!(config.placement(), Err(Error::Unavailable)));
+
+        #[cfg(feature = "dns")]
+        {
+            assert!(matches!(config.resolver(), Err(Error::Unavailable)));
+
+            let config = Config::from_vars([("FLYTRAP_DNS_SERVER", "127.0.0.1:5353")]);
+            assert!(config.resolver().is_ok());
+
+            let config = Config::from_vars([("FLY_PRIVATE_IP", "2001:db8::1")]);
+            assert!(matches!(config.resolver(), Err(Error::Address(_))));
+        }
+
+        #[cfg(feature = "api")]
+        assert!(matches!(config.client(), Err(Error::Unavailable)));
+    }
+
+    #[test]
+    #[cfg(feature = "regions")]
+    fn invalid_region() {
+        for region in ["Seattle!", "!!"] {
+            let vars = VARS.map(|(name, value)| match name {
+                "FLY_REGION" => (name, region),
+                _ => (name, value),
+            });
+
+            match Config::from_vars(vars).placement() {
+                Err(Error::Environment { name, value }) => {
+                    assert_eq!("FLY_REGION", name);
+                    assert_eq!(region, value);
+                }
+                result => panic!("expected an Environment error; got {result:?}"),
+            }
+        }
+    }
+}
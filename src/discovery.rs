@@ -0,0 +1,145 @@
+use crate::{
+    api::{Client, Machine},
+    error::Error,
+    Peer, Resolver,
+};
+
+/// Discover the [peers][Peer] of a Fly.io app, querying [internal DNS][] first
+/// and falling back to the [Machines API][] if DNS is unavailable.
+///
+/// [internal DNS]: https://fly.io/docs/reference/private-networking/#fly-internal-addresses
+/// [Machines API]: https://fly.io/docs/machines/api/
+///
+/// ```no_run
+/// use flytrap::{api::Client, Discovery, Resolver};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let resolver = Resolver::new()?;
+/// let client = Client::new(std::env::var("FLY_API_TOKEN")?);
+///
+/// let (peers, source) = Discovery::new(&resolver, &client).current_peers().await?;
+/// println!("found {} peer(s) via {source:?}", peers.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct Discovery<'a> {
+    resolver: &'a Resolver,
+    client: &'a Client,
+}
+
+impl<'a> Discovery<'a> {
+    pub fn new(resolver: &'a Resolver, client: &'a Client) -> Self {
+        Self { resolver, client }
+    }
+
+    /// Find all running instances of the named app, and the [source][PeerSource]
+    /// which listed them.
+    ///
+    /// If the DNS query fails, the running [machines][Machine] of the app are
+    /// requested from the Machines API instead; if that also fails, its error
+    /// is returned.
+    pub async fn peers(&self, app: impl AsRef<str>) -> Result<(Vec<Peer>, PeerSource), Error> {
+        let app = app.as_ref();
+
+        match self.resolver.app(app).peers().await {
+            Ok(peers) => Ok((peers, PeerSource::Dns)),
+            Err(_) => {
+                let machines = self.client.machines(app).await?;
+                let peers = machines
+                    .into_iter()
+                    .filter(Machine::is_running)
+                    .map(Peer::from)
+                    .collect();
+
+                Ok((peers, PeerSource::Api))
+            }
+        }
+    }
+
+    /// Find all running instances of the running app (as set by
+    /// `$FLY_APP_NAME`); see [`peers`][Discovery::peers].
+    ///
+    /// If `$FLY_APP_NAME` is unset, an `Unavailable` error is returned.
+    #[cfg(feature = "environment")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
+    pub async fn current_peers(&self) -> Result<(Vec<Peer>, PeerSource), Error> {
+        let app = crate::Config::from_env().app.ok_or(Error::Unavailable)?;
+        self.peers(app).await
+    }
+}
+
+/// Where [`Discovery`] found an app’s peers.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum PeerSource {
+    /// Fly.io [internal DNS](https://fly.io/docs/reference/private-networking/#fly-internal-addresses)
+    Dns,
+    /// The Fly.io [Machines API](https://fly.io/docs/machines/api/)
+    Api,
+}
+
+impl From<Machine> for Peer {
+    fn from(machine: Machine) -> Self {
+        Peer::new(machine.location, machine.id, machine.private_ip)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Discovery, PeerSource};
+    use crate::mock::{aaaa, txt, DnsServer, HttpServer, Response};
+
+    const MACHINES: &str = r#"[
+        {"id": "148e21ea7f3189", "name": "red-river-123", "state": "started", "region": "ord",
+         "instance_id": "01H", "private_ip": "fdaa:0:1:a7b:1c4::2"},
+        {"id": "d8d9e3d0a10048", "name": "blue-lake-456", "state": "stopped", "region": "ams",
+         "instance_id": "01J", "private_ip": "fdaa:0:1:a7b:1c5::3"}
+    ]"#;
+
+    #[tokio::test]
+    async fn dns() {
+        let dns = DnsServer::start(vec![
+            txt("vms.app.internal", "148e21ea7f3189 ord"),
+            aaaa(
+                "148e21ea7f3189.vm.app.internal",
+                "fdaa:0:1:a7b:1c4::2".parse().unwrap(),
+            ),
+        ])
+        .await;
+        let api = HttpServer::start(vec![Response::new(500, "")]).await;
+        let (resolver, client) = (dns.resolver(), api.client());
+
+        let (peers, source) = Discovery::new(&resolver, &client)
+            .peers("app")
+            .await
+            .unwrap();
+
+        assert_eq!(source, PeerSource::Dns);
+        assert_eq!(peers.len(), 1);
+        assert!(api.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn api_fallback() {
+        let dns = DnsServer::start(vec![]).await;
+        let api = HttpServer::start(vec![Response::new(200, MACHINES)]).await;
+        let (resolver, client) = (dns.resolver(), api.client());
+
+        let (peers, source) = Discovery::new(&resolver, &client)
+            .peers("app")
+            .await
+            .unwrap();
+
+        assert_eq!(source, PeerSource::Api);
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].id, "148e21ea7f3189");
+        assert_eq!(
+            peers[0].private_ip,
+            "fdaa:0:1:a7b:1c4::2".parse::<std::net::Ipv6Addr>().unwrap()
+        );
+        assert_eq!(
+            api.requests(),
+            vec!["GET /v1/apps/app/machines HTTP/1.1".to_owned()]
+        );
+    }
+}
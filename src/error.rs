@@ -7,7 +7,19 @@ use hickory_resolver::error::ResolveError;
 pub enum Error {
     #[cfg(feature = "api")]
     #[error(transparent)]
-    Api(#[from] reqwest::Error),
+    Api(reqwest::Error),
+    #[cfg(feature = "api")]
+    #[error("invalid Fly.io Machines API URL")]
+    Url(#[from] url::ParseError),
+    #[cfg(feature = "api")]
+    #[error("Fly.io resource not found: {resource}")]
+    NotFound { resource: String },
+    #[cfg(any(feature = "api", feature = "dns"))]
+    #[error("timed out waiting for Fly.io")]
+    Timeout,
+    #[cfg(feature = "api")]
+    #[error("Fly.io machine can't reach the {} state", .0.as_str())]
+    State(crate::api::MachineState),
     #[cfg(feature = "dns")]
     #[error(transparent)]
     Resolve(#[from] ResolveError),
@@ -15,11 +27,160 @@ pub enum Error {
     Unavailable,
     #[error("failed to parse Fly.io TXT record")]
     Parse,
+    #[error("not a Fly.io private networking address: {0}")]
+    Address(std::net::Ipv6Addr),
+    #[error("invalid ${name} environment variable: {value:?}")]
+    Environment { name: &'static str, value: String },
+    #[cfg(feature = "regions")]
+    #[error("failed to parse Fly.io region")]
+    Region(#[from] crate::region::RegionError),
 }
 
-#[cfg(feature = "regions")]
-impl From<crate::region::RegionError> for Error {
-    fn from(_value: crate::region::RegionError) -> Self {
-        Self::Parse
+#[cfg(feature = "api")]
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            Error::Timeout
+        } else {
+            Error::Api(err)
+        }
+    }
+}
+
+impl Error {
+    /// Checks if the operation which failed with this error might succeed if
+    /// tried again.
+    ///
+    /// Timeouts, connection failures, DNS `SERVFAIL` responses, and `429 Too
+    /// Many Requests` or `5xx` responses from the Machines API are retryable.
+    /// Other API errors, missing resources, and parsing errors are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            #[cfg(feature = "api")]
+            // timeouts are converted to `Error::Timeout` by `From<reqwest::Error>`
+            Error::Api(err) => {
+                err.is_connect()
+                    || err.status().is_some_and(|status| {
+                        status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+                    })
+            }
+            #[cfg(any(feature = "api", feature = "dns"))]
+            Error::Timeout => true,
+            #[cfg(feature = "dns")]
+            Error::Resolve(err) => {
+                use hickory_resolver::{
+                    error::ResolveErrorKind,
+                    proto::{error::ProtoErrorKind, op::ResponseCode},
+                };
+
+                match err.kind() {
+                    ResolveErrorKind::Timeout
+                    | ResolveErrorKind::NoConnections
+                    | ResolveErrorKind::Io(_) => true,
+                    ResolveErrorKind::NoRecordsFound { response_code, .. } => {
+                        *response_code == ResponseCode::ServFail
+                    }
+                    ResolveErrorKind::Proto(err) => {
+                        matches!(err.kind(), ProtoErrorKind::Timeout | ProtoErrorKind::Io(_))
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Error;
+
+    #[test]
+    fn not_retryable() {
+        assert!(!Error::Unavailable.is_retryable());
+        assert!(!Error::Parse.is_retryable());
+        assert!(!Error::Environment {
+            name: "FLY_REGION",
+            value: "!!".to_owned()
+        }
+        .is_retryable());
+    }
+
+    #[test]
+    #[cfg(any(feature = "api", feature = "dns"))]
+    fn timeout_retryable() {
+        assert!(Error::Timeout.is_retryable());
+    }
+
+    #[test]
+    #[cfg(feature = "dns")]
+    fn resolve_retryable() {
+        use hickory_resolver::{
+            error::{ResolveError, ResolveErrorKind},
+            proto::op::{Query, ResponseCode},
+        };
+
+        let no_records = |response_code| {
+            Error::from(ResolveError::from(ResolveErrorKind::NoRecordsFound {
+                query: Box::new(Query::new()),
+                soa: None,
+                negative_ttl: None,
+                response_code,
+                trusted: true,
+            }))
+        };
+
+        assert!(Error::from(ResolveError::from(ResolveErrorKind::Timeout)).is_retryable());
+        assert!(Error::from(ResolveError::from(ResolveErrorKind::NoConnections)).is_retryable());
+        assert!(no_records(ResponseCode::ServFail).is_retryable());
+        assert!(!no_records(ResponseCode::NXDomain).is_retryable());
+        assert!(!Error::from(ResolveError::from("invalid query")).is_retryable());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "api")]
+    async fn api_retryable() {
+        use std::time::Duration;
+
+        use crate::{
+            api::Client,
+            mock::{HttpServer, Response},
+        };
+
+        let server = HttpServer::start(vec![
+            Response::new(503, ""),
+            Response::new(429, ""),
+            Response::new(400, ""),
+            Response::new(404, ""),
+        ])
+        .await;
+        let client = server.client();
+        let mut statuses = Vec::new();
+        for _ in 0..4 {
+            let err = client
+                .machine("flytrap", "148e21ea7f3189")
+                .await
+                .unwrap_err();
+            statuses.push(err.is_retryable());
+        }
+        assert_eq!(vec![true, true, false, false], statuses);
+
+        assert!(!Error::NotFound {
+            resource: "/v1/apps/flytrap".to_owned()
+        }
+        .is_retryable());
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = Client::try_with_origin(origin, "test-token")
+            .unwrap()
+            .with_timeout(Duration::from_secs(5));
+        let err = client
+            .machine("flytrap", "148e21ea7f3189")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Api(_)), "{err:?}");
+        assert!(err.is_retryable());
     }
 }
@@ -14,7 +14,10 @@
 //! }
 //! ```
 
-use std::{fmt, net::IpAddr};
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+};
 
 use headers::{Header, HeaderName, HeaderValue};
 
@@ -23,6 +26,9 @@ use crate::{Location, Region};
 /// The [`Fly-Client-IP`][def] header: the IP address that Fly.io accepted the
 /// incoming connection from.
 ///
+/// When decoding, a port number after the address is ignored, and if several
+/// comma-separated addresses are present, the first is used.
+///
 /// [def]: https://fly.io/docs/reference/runtime-environment/#fly-client-ip
 #[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
 #[doc(alias = "Fly-Client-IP")]
@@ -51,7 +57,13 @@ impl Header for FlyClientIp {
         let value = values.next().ok_or_else(headers::Error::invalid)?;
         let value = value.to_str().map_err(|_| headers::Error::invalid())?;
 
-        let ip: IpAddr = value.parse().map_err(|_| headers::Error::invalid())?;
+        if let Ok(ip) = value.parse::<IpAddr>() {
+            return Ok(Self(ip));
+        }
+
+        // Some proxies append a port, or a list of further addresses
+        let first = value.split(',').next().unwrap_or(value).trim();
+        let ip = parse_address(first).ok_or_else(headers::Error::invalid)?;
 
         Ok(Self(ip))
     }
@@ -74,6 +86,100 @@ impl From<FlyClientIp> for HeaderValue {
     }
 }
 
+/// The `Fly-Forwarded-For` header: the chain of IP addresses a request was
+/// forwarded through, starting with the original client.
+///
+/// Entries are separated by commas; whitespace, empty entries, and any port
+/// number attached to an address (like `[2001:db8::1]:443`) are ignored.
+///
+/// ```
+/// use flytrap::http::FlyForwardedFor;
+/// use headers::{Header, HeaderValue};
+///
+/// let value = HeaderValue::from_static("203.0.113.7, 198.51.100.1:8080");
+/// let chain = FlyForwardedFor::decode(&mut std::iter::once(&value)).unwrap();
+///
+/// assert_eq!(chain.first(), Some("203.0.113.7".parse().unwrap()));
+/// assert_eq!(chain.to_string(), "203.0.113.7, 198.51.100.1");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[doc(alias = "Fly-Forwarded-For")]
+#[doc(alias = "X-Forwarded-For")]
+pub struct FlyForwardedFor(pub Vec<IpAddr>);
+
+impl FlyForwardedFor {
+    pub const fn new(chain: Vec<IpAddr>) -> Self {
+        Self(chain)
+    }
+
+    /// The first address in the chain: the client which sent the request.
+    pub fn first(&self) -> Option<IpAddr> {
+        self.0.first().copied()
+    }
+
+    pub fn into_inner(self) -> Vec<IpAddr> {
+        self.0
+    }
+}
+
+impl Header for FlyForwardedFor {
+    fn name() -> &'static HeaderName {
+        &FLY_FORWARDED_FOR
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let mut chain = Vec::new();
+
+        for value in values {
+            let value = value.to_str().map_err(|_| headers::Error::invalid())?;
+
+            for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                chain.push(parse_address(entry).ok_or_else(headers::Error::invalid)?);
+            }
+        }
+
+        if chain.is_empty() {
+            return Err(headers::Error::invalid());
+        }
+
+        Ok(Self(chain))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        values.extend(std::iter::once(HeaderValue::from(self)))
+    }
+}
+
+impl fmt::Display for FlyForwardedFor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, ip) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            ip.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<FlyForwardedFor> for HeaderValue {
+    fn from(value: FlyForwardedFor) -> Self {
+        HeaderValue::from(&value)
+    }
+}
+
+impl From<&FlyForwardedFor> for HeaderValue {
+    fn from(value: &FlyForwardedFor) -> Self {
+        HeaderValue::from_str(&value.to_string())
+            .expect("IP addresses not serializable as HeaderValue")
+    }
+}
+
 /// The [`Fly-Forwarded-Port`][def] header: the port that the client connected
 /// to the Fly.io edge.
 ///
@@ -128,9 +234,151 @@ impl From<FlyForwardedPort> for HeaderValue {
     }
 }
 
+/// The [`Fly-Forwarded-Proto`][def] header: the protocol (`http` or `https`)
+/// of the client's connection to the Fly.io edge.
+///
+/// [def]: https://fly.io/docs/reference/runtime-environment/#fly-forwarded-proto
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+#[doc(alias = "Fly-Forwarded-Proto")]
+#[doc(alias = "X-Forwarded-Proto")]
+pub enum FlyForwardedProto {
+    Http,
+    Https,
+}
+
+impl FlyForwardedProto {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+        }
+    }
+}
+
+impl Header for FlyForwardedProto {
+    fn name() -> &'static HeaderName {
+        &FLY_FORWARDED_PROTO
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+
+        match value.as_bytes() {
+            b"http" => Ok(Self::Http),
+            b"https" => Ok(Self::Https),
+            _ => Err(headers::Error::invalid()),
+        }
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        values.extend(std::iter::once(HeaderValue::from(*self)))
+    }
+}
+
+impl fmt::Display for FlyForwardedProto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<FlyForwardedProto> for HeaderValue {
+    fn from(value: FlyForwardedProto) -> Self {
+        HeaderValue::from_static(value.as_str())
+    }
+}
+
+/// The `Fly-Request-Id` header: a unique identifier that the Fly.io edge
+/// assigns to each request, for correlating logs.
+///
+/// Empty IDs, and IDs containing non-ASCII or control characters, are
+/// rejected, both when decoding and by [`FlyRequestId::new`].
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+#[doc(alias = "Fly-Request-Id")]
+pub struct FlyRequestId(String);
+
+impl FlyRequestId {
+    pub fn new(id: impl Into<String>) -> Result<Self, headers::Error> {
+        let id = id.into();
+
+        if Self::valid(id.as_bytes()) {
+            Ok(Self(id))
+        } else {
+            Err(headers::Error::invalid())
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+
+    fn valid(value: &[u8]) -> bool {
+        !value.is_empty() && value.iter().all(|b| b.is_ascii() && !b.is_ascii_control())
+    }
+}
+
+impl Header for FlyRequestId {
+    fn name() -> &'static HeaderName {
+        &FLY_REQUEST_ID
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+
+        Self::try_from(value)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        values.extend(std::iter::once(HeaderValue::from(self)))
+    }
+}
+
+impl TryFrom<&HeaderValue> for FlyRequestId {
+    type Error = headers::Error;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        let value = value.to_str().map_err(|_| headers::Error::invalid())?;
+        Self::new(value)
+    }
+}
+
+impl fmt::Display for FlyRequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<FlyRequestId> for HeaderValue {
+    fn from(value: FlyRequestId) -> Self {
+        HeaderValue::from(&value)
+    }
+}
+
+impl From<&FlyRequestId> for HeaderValue {
+    fn from(value: &FlyRequestId) -> Self {
+        HeaderValue::from_str(&value.0).expect("FlyRequestId is validated as visible ASCII")
+    }
+}
+
 /// The [`Fly-Region`][def] header: the Fly.io edge [region][Location] where the
 /// client's connection was accepted from the Internet.
 ///
+/// An empty header value decodes as [`RegionCode::UNKNOWN`][crate::RegionCode::UNKNOWN]
+/// (or an empty string, without the _regions_ feature), rather than failing;
+/// [`RegionCode::UNKNOWN`][crate::RegionCode::UNKNOWN] encodes back as an empty
+/// header value.
+///
 /// [def]: https://fly.io/docs/reference/runtime-environment/#fly-region
 #[derive(PartialEq, Eq, Clone, Debug)]
 #[cfg_attr(feature = "regions", derive(Copy))]
@@ -156,6 +404,22 @@ impl FlyRegion {
     pub const fn into_inner(self) -> Location {
         self.0
     }
+
+    /// The `Fly-Region` header name and value, for stamping a response with
+    /// the region that served it.
+    ///
+    /// ```ignore
+    /// use axum::response::IntoResponse;
+    /// use flytrap::{http::FlyRegion, Placement};
+    ///
+    /// async fn handler() -> impl IntoResponse {
+    ///     let placement = Placement::current().unwrap();
+    ///     ([FlyRegion::new(placement.location).header_pair()], "Hello!")
+    /// }
+    /// ```
+    pub fn header_pair(&self) -> (HeaderName, HeaderValue) {
+        (FLY_REGION.clone(), HeaderValue::from(self))
+    }
 }
 
 impl Header for FlyRegion {
@@ -172,7 +436,11 @@ impl Header for FlyRegion {
         let value = value.to_str().map_err(|_| headers::Error::invalid())?;
 
         #[cfg(feature = "regions")]
-        let region: Location = value.parse().map_err(|_| headers::Error::invalid())?;
+        let region: Location = if value.trim().is_empty() {
+            Location::Unknown(crate::RegionCode::UNKNOWN)
+        } else {
+            value.parse().map_err(|_| headers::Error::invalid())?
+        };
 
         #[cfg(not(feature = "regions"))]
         let region = value.to_owned();
@@ -194,12 +462,7 @@ impl fmt::Display for FlyRegion {
 #[cfg(feature = "regions")]
 impl From<FlyRegion> for HeaderValue {
     fn from(region: FlyRegion) -> Self {
-        match region.0 {
-            Location::Region(region) => HeaderValue::from_static(region.code),
-            Location::Unknown(ref code) => {
-                HeaderValue::from_bytes(code.as_ref()).expect("invalid Fly.io region code")
-            }
-        }
+        HeaderValue::from(&region)
     }
 }
 
@@ -208,6 +471,7 @@ impl From<&FlyRegion> for HeaderValue {
         #[cfg(feature = "regions")]
         let value = match region.0 {
             Location::Region(region) => HeaderValue::from_static(region.code),
+            Location::Unknown(crate::RegionCode::UNKNOWN) => HeaderValue::from_static(""),
             Location::Unknown(ref code) => {
                 HeaderValue::from_bytes(code.as_ref()).expect("invalid Fly.io region code")
             }
@@ -221,17 +485,304 @@ impl From<&FlyRegion> for HeaderValue {
     }
 }
 
+/// The Fly.io edge proxy headers of a request, extracted together by an
+/// [axum][] handler.
+///
+/// [axum]: https://lib.rs/crates/axum
+///
+/// `Fly-Client-IP` is required; if it's missing, the request is rejected with
+/// `400 Bad Request`. The other headers are optional, and their fields are
+/// `None` when the header is absent. Any header which is present but can't be
+/// decoded also rejects the request.
+///
+/// ```ignore
+/// use flytrap::http::FlyClient;
+///
+/// async fn handler(client: FlyClient) -> String {
+///     format!("Hello, {} (via {:?})", client.ip, client.region)
+/// }
+/// ```
+#[cfg(feature = "axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct FlyClient {
+    /// The [`Fly-Client-IP`][FlyClientIp] header.
+    pub ip: IpAddr,
+    /// The [`Fly-Region`][FlyRegion] header, if present.
+    pub region: Option<Location>,
+    /// The [`Fly-Forwarded-Port`][FlyForwardedPort] header, if present.
+    pub port: Option<u16>,
+    /// The [`Fly-Request-Id`][FlyRequestId] header, if present.
+    pub request_id: Option<FlyRequestId>,
+}
+
+#[cfg(feature = "axum")]
+#[axum::async_trait]
+impl<S: Send + Sync> axum::extract::FromRequestParts<S> for FlyClient {
+    type Rejection = (http::StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let headers = &parts.headers;
+
+        let FlyClientIp(ip) = decode_header(headers)?.ok_or_else(|| {
+            (
+                http::StatusCode::BAD_REQUEST,
+                format!("missing {} header", FLY_CLIENT_IP),
+            )
+        })?;
+
+        Ok(Self {
+            ip,
+            region: decode_header::<FlyRegion>(headers)?.map(|r| r.0),
+            port: decode_header::<FlyForwardedPort>(headers)?.map(|p| p.0),
+            request_id: decode_header(headers)?,
+        })
+    }
+}
+
+/// Decode the typed header `H`, if the request has it.
+#[cfg(feature = "axum")]
+fn decode_header<H: Header>(
+    headers: &http::HeaderMap,
+) -> Result<Option<H>, (http::StatusCode, String)> {
+    let mut values = headers.get_all(H::name()).iter().peekable();
+    if values.peek().is_none() {
+        return Ok(None);
+    }
+
+    H::decode(&mut values).map(Some).map_err(|_| {
+        (
+            http::StatusCode::BAD_REQUEST,
+            format!("invalid {} header", H::name()),
+        )
+    })
+}
+
+/// Parse an IP address, ignoring any port number (as in `1.2.3.4:443` or
+/// `[2001:db8::1]:443`).
+fn parse_address(value: &str) -> Option<IpAddr> {
+    value
+        .parse::<IpAddr>()
+        .or_else(|_| value.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .ok()
+}
+
 /// The [name][HeaderName] for the [`Fly-Client-IP`][def] HTTP header.
 ///
 /// [def]: https://fly.io/docs/reference/runtime-environment/#fly-client-ip
 pub static FLY_CLIENT_IP: HeaderName = HeaderName::from_static("fly-client-ip");
 
+/// The [name][HeaderName] for the `Fly-Forwarded-For` HTTP header.
+pub static FLY_FORWARDED_FOR: HeaderName = HeaderName::from_static("fly-forwarded-for");
+
 /// The [name][HeaderName] for the [`Fly-Forwarded-Port`][def] HTTP header.
 ///
 /// [def]: https://fly.io/docs/reference/runtime-environment/#fly-forwarded-port
 pub static FLY_FORWARDED_PORT: HeaderName = HeaderName::from_static("fly-forwarded-port");
 
+/// The [name][HeaderName] for the [`Fly-Forwarded-Proto`][def] HTTP header.
+///
+/// [def]: https://fly.io/docs/reference/runtime-environment/#fly-forwarded-proto
+pub static FLY_FORWARDED_PROTO: HeaderName = HeaderName::from_static("fly-forwarded-proto");
+
+/// The [name][HeaderName] for the `Fly-Request-Id` HTTP header.
+pub static FLY_REQUEST_ID: HeaderName = HeaderName::from_static("fly-request-id");
+
 /// The [name][HeaderName] for the [`Fly-Region`][def] HTTP header.
 ///
 /// [def]: https://fly.io/docs/reference/runtime-environment/#fly-region
 pub static FLY_REGION: HeaderName = HeaderName::from_static("fly-region");
+
+#[cfg(test)]
+mod test {
+    use headers::{Header, HeaderValue};
+
+    use super::{
+        FlyClientIp, FlyForwardedFor, FlyForwardedProto, FlyRegion, FlyRequestId, FLY_REGION,
+    };
+
+    fn decode<H: Header>(values: &[&'static str]) -> Result<H, headers::Error> {
+        let values: Vec<HeaderValue> = values.iter().map(|v| HeaderValue::from_static(v)).collect();
+        H::decode(&mut values.iter())
+    }
+
+    #[test]
+    fn client_ip() {
+        let ip = |s: &str| s.parse::<std::net::IpAddr>().unwrap();
+
+        let decoded: FlyClientIp = decode(&["1.2.3.4"]).unwrap();
+        assert_eq!(ip("1.2.3.4"), decoded.0);
+
+        let decoded: FlyClientIp = decode(&["2001:db8::1"]).unwrap();
+        assert_eq!(ip("2001:db8::1"), decoded.0);
+
+        let decoded: FlyClientIp = decode(&["1.2.3.4:443"]).unwrap();
+        assert_eq!(ip("1.2.3.4"), decoded.0);
+
+        let decoded: FlyClientIp = decode(&["[2001:db8::1]:443"]).unwrap();
+        assert_eq!(ip("2001:db8::1"), decoded.0);
+
+        let decoded: FlyClientIp = decode(&["1.2.3.4, 5.6.7.8"]).unwrap();
+        assert_eq!(ip("1.2.3.4"), decoded.0);
+
+        assert!(decode::<FlyClientIp>(&["unknown"]).is_err());
+        assert!(decode::<FlyClientIp>(&[", 1.2.3.4"]).is_err());
+    }
+
+    #[test]
+    fn forwarded_for() {
+        let chain: FlyForwardedFor = decode(&["203.0.113.7"]).unwrap();
+        assert_eq!(
+            vec!["203.0.113.7".parse::<std::net::IpAddr>().unwrap()],
+            chain.0
+        );
+        assert_eq!("203.0.113.7", HeaderValue::from(&chain));
+
+        let chain: FlyForwardedFor = decode(&[" 203.0.113.7 ,, 198.51.100.1,"]).unwrap();
+        assert_eq!(Some("203.0.113.7".parse().unwrap()), chain.first());
+        assert_eq!("203.0.113.7, 198.51.100.1", HeaderValue::from(chain));
+
+        let chain: FlyForwardedFor =
+            decode(&["[2001:db8::1]:443, 2001:db8::2", "198.51.100.1:8080"]).unwrap();
+        assert_eq!(
+            vec![
+                "2001:db8::1".parse::<std::net::IpAddr>().unwrap(),
+                "2001:db8::2".parse().unwrap(),
+                "198.51.100.1".parse().unwrap(),
+            ],
+            chain.into_inner()
+        );
+
+        assert!(decode::<FlyForwardedFor>(&[" , "]).is_err());
+        assert!(decode::<FlyForwardedFor>(&["203.0.113.7, unknown"]).is_err());
+    }
+
+    #[test]
+    fn forwarded_proto() {
+        assert_eq!(FlyForwardedProto::Https, decode(&["https"]).unwrap());
+        assert_eq!(FlyForwardedProto::Http, decode(&["http"]).unwrap());
+        assert!(decode::<FlyForwardedProto>(&["ftp"]).is_err());
+        assert!(decode::<FlyForwardedProto>(&["https "]).is_err());
+        assert!(decode::<FlyForwardedProto>(&[]).is_err());
+
+        assert_eq!("https", FlyForwardedProto::Https.to_string());
+        assert_eq!("http", HeaderValue::from(FlyForwardedProto::Http));
+    }
+
+    #[test]
+    fn request_id() {
+        let id: FlyRequestId = decode(&["01HF5D6Y5B9Z-ord"]).unwrap();
+        assert_eq!("01HF5D6Y5B9Z-ord", id.as_str());
+        assert_eq!("01HF5D6Y5B9Z-ord", HeaderValue::from(&id));
+        assert_eq!("01HF5D6Y5B9Z-ord", id.into_inner());
+
+        assert!(decode::<FlyRequestId>(&[""]).is_err());
+        assert!(decode::<FlyRequestId>(&["01HF\t5D6"]).is_err());
+        assert!(
+            FlyRequestId::try_from(&HeaderValue::from_bytes(b"01HF\xc3\xa9").unwrap()).is_err()
+        );
+
+        let id = FlyRequestId::new("01HF5D6Y5B9Z-ord").unwrap();
+        assert_eq!("01HF5D6Y5B9Z-ord", HeaderValue::from(id));
+        assert!(FlyRequestId::new("a\nb").is_err());
+        assert!(FlyRequestId::new("").is_err());
+        assert!(FlyRequestId::new("01HFé").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "regions")]
+    fn region_decode() {
+        use crate::{Location, Region, RegionCode};
+
+        let region: FlyRegion = decode(&["ord"]).unwrap();
+        assert_eq!(Location::Region(Region::Chicago), region.0);
+
+        let region: FlyRegion = decode(&["cai"]).unwrap();
+        assert_eq!(Location::Unknown("cai".parse().unwrap()), region.0);
+
+        let region: FlyRegion = decode(&[""]).unwrap();
+        assert_eq!(Location::Unknown(RegionCode::UNKNOWN), region.0);
+        assert_eq!("???", region.to_string());
+        assert_eq!("", region.header_pair().1);
+
+        assert!(decode::<FlyRegion>(&["chicago!"]).is_err());
+        assert!(decode::<FlyRegion>(&["OR"]).is_err());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "axum")]
+    async fn fly_client() {
+        use axum::extract::FromRequestParts;
+
+        use super::FlyClient;
+
+        async fn extract(headers: &[(&str, &str)]) -> Result<FlyClient, http::StatusCode> {
+            let mut request = http::Request::builder();
+            for (name, value) in headers {
+                request = request.header(*name, *value);
+            }
+            let (mut parts, ()) = request.body(()).unwrap().into_parts();
+
+            FlyClient::from_request_parts(&mut parts, &())
+                .await
+                .map_err(|(status, _)| status)
+        }
+
+        let client = extract(&[
+            ("fly-client-ip", "203.0.113.7"),
+            ("fly-region", "ord"),
+            ("fly-forwarded-port", "443"),
+            ("fly-request-id", "01HF5D6Y5B9Z-ord"),
+        ])
+        .await
+        .unwrap();
+        assert_eq!(
+            "203.0.113.7".parse::<std::net::IpAddr>().unwrap(),
+            client.ip
+        );
+        assert_eq!(Some("ord".parse().unwrap()), client.region);
+        assert_eq!(Some(443), client.port);
+        assert_eq!(
+            Some(FlyRequestId::new("01HF5D6Y5B9Z-ord").unwrap()),
+            client.request_id
+        );
+
+        let client = extract(&[("fly-client-ip", "2001:db8::1")]).await.unwrap();
+        assert_eq!(
+            (None, None, None),
+            (client.region, client.port, client.request_id)
+        );
+
+        assert_eq!(
+            Err(http::StatusCode::BAD_REQUEST),
+            extract(&[("fly-region", "ord")]).await
+        );
+        assert_eq!(
+            Err(http::StatusCode::BAD_REQUEST),
+            extract(&[
+                ("fly-client-ip", "203.0.113.7"),
+                ("fly-forwarded-port", "https")
+            ])
+            .await
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regions")]
+    fn region_header_pair() {
+        use crate::Region;
+
+        let (name, value) = FlyRegion::new(Region::Madrid.into()).header_pair();
+
+        assert_eq!(FLY_REGION, name);
+        assert_eq!("fly-region", name.as_str());
+        assert_eq!("mad", value);
+
+        let (_, value) = FlyRegion::new("cai".parse().unwrap()).header_pair();
+        assert_eq!("cai", value);
+    }
+}
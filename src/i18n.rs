@@ -0,0 +1,104 @@
+use crate::Region;
+
+impl Region {
+    /// The name of the region's [city][crate::City] in the given language,
+    /// falling back to its English name if no translation is known.
+    ///
+    /// `lang` is a [BCP 47][] language tag like `de` or `pt-BR`; only its
+    /// primary language subtag is considered. German (`de`), Spanish (`es`),
+    /// French (`fr`), Italian (`it`), and Portuguese (`pt`) names are
+    /// included.
+    ///
+    /// [BCP 47]: https://www.rfc-editor.org/info/bcp47
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// assert_eq!(Region::Warsaw.name_in("fr"), "Varsovie");
+    /// assert_eq!(Region::London.name_in("es-MX"), "Londres");
+    /// assert_eq!(Region::London.name_in("nl"), "London");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "i18n")))]
+    pub fn name_in(&self, lang: &str) -> &'static str {
+        let primary = lang.split(['-', '_']).next().unwrap_or(lang);
+
+        translate(*self, &primary.to_ascii_lowercase()).unwrap_or(self.details().city.name)
+    }
+}
+
+/// City names which differ from their English names.
+fn translate(region: Region, lang: &str) -> Option<&'static str> {
+    use Region::*;
+
+    let name = match (lang, region) {
+        ("de", Bucharest) => "Bukarest",
+        ("de", HongKong) => "Hongkong",
+        ("de", SaoPaulo) => "São Paulo",
+        ("de", Singapore) => "Singapur",
+        ("de", Tokyo) => "Tokio",
+        ("de", Warsaw) => "Warschau",
+
+        ("es", Amsterdam) => "Ámsterdam",
+        ("es", Bucharest) => "Bucarest",
+        ("es", Frankfurt) => "Fráncfort",
+        ("es", Johannesburg) => "Johannesburgo",
+        ("es", London) => "Londres",
+        ("es", Paris) => "París",
+        ("es", RioDeJaneiro) => "Río de Janeiro",
+        ("es", SaoPaulo) => "São Paulo",
+        ("es", Singapore) => "Singapur",
+        ("es", Stockholm) => "Estocolmo",
+        ("es", Sydney) => "Sídney",
+        ("es", Tokyo) => "Tokio",
+        ("es", Warsaw) => "Varsovia",
+
+        ("fr", Bogota) => "Bogota",
+        ("fr", Bucharest) => "Bucarest",
+        ("fr", Frankfurt) => "Francfort",
+        ("fr", Johannesburg) => "Johannesbourg",
+        ("fr", London) => "Londres",
+        ("fr", Montreal) => "Montréal",
+        ("fr", SaoPaulo) => "São Paulo",
+        ("fr", Singapore) => "Singapour",
+        ("fr", Warsaw) => "Varsovie",
+
+        ("it", Bucharest) => "Bucarest",
+        ("it", Frankfurt) => "Francoforte",
+        ("it", London) => "Londra",
+        ("it", Paris) => "Parigi",
+        ("it", SaoPaulo) => "San Paolo",
+        ("it", Stockholm) => "Stoccolma",
+        ("it", Warsaw) => "Varsavia",
+
+        ("pt", Bucharest) => "Bucareste",
+        ("pt", Johannesburg) => "Joanesburgo",
+        ("pt", London) => "Londres",
+        ("pt", SaoPaulo) => "São Paulo",
+        ("pt", Singapore) => "Singapura",
+        ("pt", Stockholm) => "Estocolmo",
+        ("pt", Tokyo) => "Tóquio",
+        ("pt", Warsaw) => "Varsóvia",
+
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Region;
+
+    #[test]
+    fn name_in() {
+        assert_eq!("Tokio", Region::Tokyo.name_in("de"));
+        assert_eq!("Tóquio", Region::Tokyo.name_in("pt-BR"));
+        assert_eq!("Montréal", Region::Montreal.name_in("FR_ca"));
+        assert_eq!("Parigi", Region::Paris.name_in("it"));
+
+        assert_eq!("Paris", Region::Paris.name_in("de"));
+        assert_eq!("Tokyo", Region::Tokyo.name_in("en"));
+        assert_eq!("Tokyo", Region::Tokyo.name_in("tlh"));
+        assert_eq!("Tokyo", Region::Tokyo.name_in(""));
+    }
+}
@@ -68,7 +68,7 @@
 //!
 //! fn show(region: Region) {
 //!     let City { name, country, geo } = region.city;
-//!     println!("Running in {name} ({country}) @ {}, {}", geo.x(), geo.y());
+//!     println!("Running in {name} ({country}) @ {}, {}", geo.y(), geo.x()); // lat, long
 //! }
 //! ```
 //!
@@ -134,6 +134,10 @@
 //! }
 //! ```
 //!
+//! With both the _api_ and _dns_ features enabled, [`Discovery`] queries
+//! internal DNS for an app’s peers, and falls back to the machines API if DNS
+//! is unavailable.
+//!
 //! ### HTTP headers
 //!
 //! The [`http`][http] module contains typed [`Header`][headers] implementations of
@@ -150,17 +154,23 @@
 //! [Cargo features]: https://doc.rust-lang.org/cargo/reference/features.html
 //!
 //! - **`api`**: Enable the [client][`api`] for the Fly.io [machines API][]
+//! - **`axum`**: Enable the [`FlyClient`][http::FlyClient] [axum][] extractor for Fly.io request headers
+//! - **`chrono`**: Parse Machines API timestamps into [`chrono`][chrono] date-times
 //! - **`dns`**: Enable [`Resolver`] for querying Fly.io [internal DNS][dns], via [`hickory-dns`][hickory] ⭐
 //! - **`detect`**: Enable automatic [`Resolver`] setup for Wireguard VPN clients, via [`if-addrs`][if-addrs] ⭐️
 //! - **`environment`**: Enable code which reads Fly.io environment variables like `$FLY_PUBLIC_IP` ⭐️
 //! - **`http`**: Enable types for HTTP [`headers`][headers] like [`Fly-Client-IP`][http::FlyClientIp] ⭐️
+//! - **`i18n`**: Enable [`Region::name_in`], which translates region city names into a few common languages
 //! - **`nightly`**: Enable code which is only accepted by nightly Rust toolchains
+//! - **`reachability`**: Enable [`Peer::is_reachable_sync`], a blocking TCP connection check
 //! - **`regions`**: Enable the [`Region`] type and [`RegionDetails`] structures ⭐️
 //! - **`serde`**: Enable [Serde][serde] `#[derive(Deserialize, Serialize)]` on this crate’s types
 //! - **`system-resolver`**: Enable the [`Resolver::system()`][Resolver::system] constructor, which reads `/etc/resolv.conf`
 //!
 //! _(Features marked with a ⭐️ are enabled by default.)_
 //!
+//! [axum]: https://lib.rs/crates/axum
+//! [chrono]: https://lib.rs/crates/chrono
 //! [headers]: https://docs.rs/headers/latest/headers/trait.Header.html
 //! [hickory]: https://lib.rs/crates/hickory-resolver
 //! [if-addrs]: https://lib.rs/crates/if-addrs
@@ -171,10 +181,17 @@
 pub mod api;
 #[cfg(feature = "dns")]
 mod app;
+mod config;
+#[cfg(all(feature = "api", feature = "dns"))]
+mod discovery;
 mod error;
 #[cfg(feature = "http")]
 #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
 pub mod http;
+#[cfg(feature = "i18n")]
+mod i18n;
+#[cfg(test)]
+mod mock;
 mod placement;
 #[cfg(feature = "regions")]
 mod region;
@@ -184,7 +201,11 @@ mod resolver;
 #[cfg(feature = "dns")]
 #[cfg_attr(docsrs, doc(cfg(feature = "dns")))]
 pub use app::AppResolver;
+#[cfg(all(feature = "api", feature = "dns"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "api", feature = "dns"))))]
+pub use discovery::{Discovery, PeerSource};
 
+pub use config::Config;
 pub use error::Error;
 
 #[cfg(feature = "environment")]
@@ -193,14 +214,17 @@ pub use placement::hosted;
 #[cfg(any(feature = "detect", feature = "environment"))]
 #[cfg_attr(docsrs, doc(cfg(any(feature = "detect", feature = "environment"))))]
 pub use placement::private_address;
-pub use placement::{Machine, Placement};
+pub use placement::{Machine, MachineId, Placement, ProcessGroup};
 
 #[cfg(feature = "regions")]
 #[cfg_attr(docsrs, doc(cfg(feature = "regions")))]
-pub use region::{City, Location, Region, RegionCode, RegionDetails, RegionError};
+pub use region::{
+    regions_by_country, Capabilities, City, Continent, DetailedRegion, Location, Region,
+    RegionCode, RegionDetails, RegionError, RegionSet,
+};
 #[cfg(feature = "dns")]
 #[cfg_attr(docsrs, doc(cfg(feature = "dns")))]
-pub use resolver::{dns_server_address, Instance, Node, Peer, Resolver};
+pub use resolver::{dns_server_address, try_dns_server_address, Instance, Node, Peer, Resolver};
 
 #[cfg(not(feature = "regions"))]
 pub type Location = String;
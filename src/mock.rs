@@ -0,0 +1,352 @@
+//! Local stand-ins for Fly.io services, used by this crate's tests.
+
+#[cfg(feature = "api")]
+pub(crate) use api::{HttpServer, Response};
+#[cfg(feature = "dns")]
+pub(crate) use dns::{aaaa, srv, txt, DnsServer};
+
+#[cfg(feature = "api")]
+mod api {
+    use std::{
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        task::JoinHandle,
+    };
+
+    use crate::api::Client;
+
+    /// An HTTP server on `localhost` which replies to each request with the
+    /// next of a fixed list of responses (repeating the last one), and
+    /// records the requests it receives.
+    pub(crate) struct HttpServer {
+        addr: SocketAddr,
+        requests: Arc<Mutex<Vec<String>>>,
+        task: JoinHandle<()>,
+    }
+
+    impl HttpServer {
+        pub async fn start(responses: Vec<Response>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let requests = Arc::new(Mutex::new(Vec::new()));
+
+            let task = tokio::spawn({
+                let requests = requests.clone();
+                async move {
+                    let mut index = 0;
+
+                    while let Ok((mut stream, _)) = listener.accept().await {
+                        let Some(request) = read_request(&mut stream).await else {
+                            continue;
+                        };
+                        requests.lock().unwrap().push(request);
+
+                        let response = &responses[index.min(responses.len() - 1)];
+                        index += 1;
+
+                        if let Some(delay) = response.delay {
+                            tokio::time::sleep(delay).await;
+                        }
+
+                        let _ = stream.write_all(response.to_string().as_bytes()).await;
+                        let _ = stream.shutdown().await;
+                    }
+                }
+            });
+
+            Self {
+                addr,
+                requests,
+                task,
+            }
+        }
+
+        /// The `http://` origin of this server.
+        pub fn origin(&self) -> String {
+            format!("http://{}", self.addr)
+        }
+
+        /// A Machines API [`Client`] which sends its requests to this server.
+        pub fn client(&self) -> Client {
+            Client::with_origin(self.origin(), "test-token")
+        }
+
+        /// The request lines (like `GET /v1/apps HTTP/1.1`) received so far.
+        pub fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl Drop for HttpServer {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    /// A canned HTTP response.
+    #[derive(Clone, Debug)]
+    pub(crate) struct Response {
+        status: u16,
+        headers: Vec<(&'static str, String)>,
+        body: String,
+        delay: Option<Duration>,
+    }
+
+    impl Response {
+        pub fn new(status: u16, body: impl Into<String>) -> Self {
+            Self {
+                status,
+                headers: vec![("Content-Type", "application/json".to_owned())],
+                body: body.into(),
+                delay: None,
+            }
+        }
+
+        /// Wait for `delay` before sending this response.
+        pub fn delayed(self, delay: Duration) -> Self {
+            Self {
+                delay: Some(delay),
+                ..self
+            }
+        }
+
+        pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+            self.headers.push((name, value.into()));
+            self
+        }
+    }
+
+    impl std::fmt::Display for Response {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "HTTP/1.1 {} Mock\r\n", self.status)?;
+            for (name, value) in &self.headers {
+                write!(f, "{name}: {value}\r\n")?;
+            }
+            write!(
+                f,
+                "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                self.body.len(),
+                self.body
+            )
+        }
+    }
+
+    /// Read an HTTP/1.1 request, and return its request line.
+    async fn read_request(stream: &mut TcpStream) -> Option<String> {
+        let mut buf = Vec::with_capacity(1024);
+        let mut chunk = [0u8; 1024];
+
+        let head = loop {
+            let n = stream.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                return None;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+
+        let text = String::from_utf8_lossy(&buf[..head]).into_owned();
+        let length = text
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+
+        while buf.len() < head + length {
+            let n = stream.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        text.lines().next().map(ToOwned::to_owned)
+    }
+}
+
+#[cfg(feature = "dns")]
+mod dns {
+    use std::{
+        net::{Ipv6Addr, SocketAddr},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use hickory_resolver::proto::{
+        op::{Message, MessageType, ResponseCode},
+        rr::{
+            rdata::{AAAA, SRV, TXT},
+            Name, RData, Record,
+        },
+        serialize::binary::BinEncodable,
+    };
+    use tokio::{net::UdpSocket, task::JoinHandle};
+
+    use crate::Resolver;
+
+    /// A DNS server on `localhost` which answers queries from a fixed set of
+    /// records, and records the queries it receives.
+    pub(crate) struct DnsServer {
+        addr: SocketAddr,
+        queries: Arc<Mutex<Vec<String>>>,
+        peak: Arc<AtomicUsize>,
+        task: JoinHandle<()>,
+    }
+
+    impl DnsServer {
+        pub async fn start(records: Vec<Record>) -> Self {
+            Self::start_delayed(records, Duration::ZERO).await
+        }
+
+        /// Start a server which waits for `delay` before answering each query.
+        pub async fn start_delayed(records: Vec<Record>, delay: Duration) -> Self {
+            let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+            let addr = socket.local_addr().unwrap();
+            let records = Arc::new(records);
+            let queries = Arc::new(Mutex::new(Vec::new()));
+            let active = Arc::new(AtomicUsize::new(0));
+            let peak = Arc::new(AtomicUsize::new(0));
+
+            let task = tokio::spawn({
+                let queries = queries.clone();
+                let peak = peak.clone();
+
+                async move {
+                    let mut buf = [0u8; 4096];
+
+                    while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                        let Ok(request) = Message::from_vec(&buf[..len]) else {
+                            continue;
+                        };
+
+                        queries.lock().unwrap().extend(
+                            request
+                                .queries()
+                                .iter()
+                                .map(|query| format!("{} {}", query.name(), query.query_type())),
+                        );
+
+                        let current = active.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(current, Ordering::SeqCst);
+
+                        let (socket, records, active) =
+                            (socket.clone(), records.clone(), active.clone());
+                        tokio::spawn(async move {
+                            tokio::time::sleep(delay).await;
+
+                            let response = Self::answer(&request, &records);
+                            active.fetch_sub(1, Ordering::SeqCst);
+                            let _ = socket.send_to(&response.to_bytes().unwrap(), peer).await;
+                        });
+                    }
+                }
+            });
+
+            Self {
+                addr,
+                queries,
+                peak,
+                task,
+            }
+        }
+
+        /// The address this server is listening on.
+        pub fn addr(&self) -> SocketAddr {
+            self.addr
+        }
+
+        /// A [`Resolver`] which sends its queries to this server.
+        pub fn resolver(&self) -> Resolver {
+            Resolver::with_source(self.addr, None)
+        }
+
+        /// The queries received so far, as `<name> <type>`.
+        pub fn queries(&self) -> Vec<String> {
+            self.queries.lock().unwrap().clone()
+        }
+
+        /// The most queries this server has been answering at once.
+        pub fn peak_concurrency(&self) -> usize {
+            self.peak.load(Ordering::SeqCst)
+        }
+
+        fn answer(request: &Message, records: &[Record]) -> Message {
+            let mut response = Message::new();
+            response
+                .set_id(request.id())
+                .set_message_type(MessageType::Response)
+                .set_op_code(request.op_code())
+                .set_recursion_desired(request.recursion_desired())
+                .set_recursion_available(true)
+                .set_authoritative(true)
+                .add_queries(request.queries().to_vec());
+
+            let answers: Vec<Record> = request
+                .queries()
+                .iter()
+                .flat_map(|query| {
+                    records.iter().filter(move |record| {
+                        record.name() == query.name() && record.record_type() == query.query_type()
+                    })
+                })
+                .cloned()
+                .collect();
+
+            if answers.is_empty() {
+                response.set_response_code(ResponseCode::NXDomain);
+            } else {
+                response.add_answers(answers);
+            }
+
+            response
+        }
+    }
+
+    impl Drop for DnsServer {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    /// A `TXT` record, split into strings of at most 255 bytes.
+    pub(crate) fn txt(name: &str, value: &str) -> Record {
+        let strings = value
+            .as_bytes()
+            .chunks(255)
+            .map(|chunk| String::from_utf8(chunk.to_vec()).unwrap())
+            .collect();
+
+        Record::from_rdata(fqdn(name), 0, RData::TXT(TXT::new(strings)))
+    }
+
+    pub(crate) fn aaaa(name: &str, ip: Ipv6Addr) -> Record {
+        Record::from_rdata(fqdn(name), 0, RData::AAAA(AAAA(ip)))
+    }
+
+    pub(crate) fn srv(name: &str, port: u16, target: &str) -> Record {
+        Record::from_rdata(
+            fqdn(name),
+            0,
+            RData::SRV(SRV::new(0, 0, port, fqdn(target))),
+        )
+    }
+
+    fn fqdn(name: &str) -> Name {
+        let mut name = Name::from_ascii(name).unwrap();
+        name.set_fqdn(true);
+        name
+    }
+}
@@ -1,11 +1,37 @@
-use std::net::{IpAddr, Ipv6Addr};
+use std::{
+    fmt,
+    net::{IpAddr, Ipv6Addr},
+    ops::Deref,
+    str::FromStr,
+};
 
-use crate::{error::Error, Location, Region};
+use crate::{error::Error, Config, Location, Region};
 
 /// Details how the current process is running in the Fly.io [runtime environment][].
 ///
+/// With the _serde_ feature, a `Placement` serializes to (and deserializes
+/// from) an object with its field names, where addresses are strings and
+/// `location` is the region code:
+///
+/// ```json
+/// {
+///   "app": "flytrap",
+///   "process_group": "app",
+///   "public_ip": "2605:4c40:95:dfa1:0:dc2c:88b9:1",
+///   "private_ip": "fdaa:0:18:a7b:d6b:0:a:2",
+///   "allocation": "148e21dad76789",
+///   "machine": {
+///     "id": "148e21dad76789",
+///     "image": null,
+///     "version": "01HF8P5FVXAA8K5QWWYJ8JBVBE",
+///     "memory": 256
+///   },
+///   "location": "sea"
+/// }
+/// ```
+///
 /// [runtime environment]: https://fly.io/docs/reference/runtime-environment/
-#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Placement {
     /// The Fly.io application name ([`$FLY_APP_NAME`][def]).
@@ -63,28 +89,80 @@ impl Placement {
     #[cfg(feature = "environment")]
     #[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
     pub fn current() -> Result<Self, Error> {
-        let app = var("FLY_APP_NAME")?;
-        let process_group = std::env::var("FLY_PROCESS_GROUP").ok();
-        let public_ip = public_address();
-        let private_ip = environment_address().ok_or(Error::Unavailable)?;
-        let allocation = var("FLY_ALLOC_ID")?;
-        let machine = Machine::current().ok();
-        let region_code = var("FLY_REGION")?;
+        Self::from_env(|name| std::env::var(name).ok())
+    }
 
-        #[cfg(feature = "regions")]
-        let location: Location = region_code.parse().expect("invalid $FLY_REGION");
-        #[cfg(not(feature = "regions"))]
-        let location = region_code;
-
-        Ok(Self {
-            app,
-            process_group,
-            public_ip,
-            private_ip,
-            allocation,
-            machine,
-            location,
-        })
+    /// Build a [`Placement`] from `$FLY_` variables returned by `vars`, rather
+    /// than the process environment; `vars` returns `None` for unset variables.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use flytrap::Placement;
+    ///
+    /// let vars = HashMap::from([
+    ///     ("FLY_APP_NAME", "flytrap"),
+    ///     ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+    ///     ("FLY_ALLOC_ID", "148e21dad76789"),
+    ///     ("FLY_REGION", "sea"),
+    /// ]);
+    /// let placement = Placement::from_env(|name| vars.get(name).map(|v| v.to_string())).unwrap();
+    ///
+    /// assert_eq!(placement.app, "flytrap");
+    /// ```
+    pub fn from_env(vars: impl Fn(&str) -> Option<String>) -> Result<Self, Error> {
+        Config::from_lookup(vars).placement()
+    }
+
+    /// Find the public IPv4 address of this Fly.io app, by listing its [IP
+    /// assignments][crate::api::Client::ip_assignments] with the Machines API.
+    ///
+    /// Fly.io doesn't set an environment variable for IPv4 addresses, so this
+    /// requires an API token. A dedicated address is preferred over a shared
+    /// one; if the app has neither, returns `None`.
+    #[cfg(feature = "api")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api")))]
+    pub async fn public_ipv4(
+        &self,
+        client: &crate::api::Client,
+    ) -> Result<Option<std::net::Ipv4Addr>, Error> {
+        let mut addresses: Vec<(bool, std::net::Ipv4Addr)> = client
+            .ip_assignments(&self.app)
+            .await?
+            .into_iter()
+            .filter_map(|assignment| match assignment.ip {
+                IpAddr::V4(ip) => Some((assignment.shared, ip)),
+                IpAddr::V6(_) => None,
+            })
+            .collect();
+        addresses.sort_by_key(|&(shared, _)| shared);
+
+        Ok(addresses.first().map(|&(_, ip)| ip))
+    }
+
+    /// Checks if this process belongs to the named [process group][ProcessGroup].
+    /// Group names are compared case-sensitively.
+    ///
+    /// Returns `false` if `$FLY_PROCESS_GROUP` is unset.
+    pub fn in_process_group(&self, name: &str) -> bool {
+        self.process_group.as_deref() == Some(name)
+    }
+
+    /// The [process group][Self::process_group] of this process, as a
+    /// [`ProcessGroup`].
+    pub fn group(&self) -> Option<ProcessGroup> {
+        self.process_group.clone().map(ProcessGroup::from)
+    }
+
+    /// The [`MachineId`] of this Fly.io machine, from [`machine`][Self::machine]
+    /// if set, or else the [`allocation`][Self::allocation] ID.
+    ///
+    /// Returns `None` for legacy (Nomad) allocation IDs, which are not machine
+    /// IDs.
+    pub fn machine_id(&self) -> Option<MachineId> {
+        match &self.machine {
+            Some(machine) => machine.id.parse().ok(),
+            None => self.allocation.parse().ok(),
+        }
     }
 
     /// The Fly.io runtime [region][], as a [`Region`][] enum.
@@ -103,7 +181,7 @@ impl Placement {
 /// is running.
 ///
 /// [machine]: https://fly.io/docs/machines/
-#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Machine {
     /// The unique ID of this Fly.io Machine ([`$FLY_MACHINE_ID`][def]).
@@ -139,19 +217,147 @@ impl Machine {
     #[cfg(feature = "environment")]
     #[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
     pub fn current() -> Result<Self, Error> {
-        let id = var("FLY_MACHINE_ID")?;
-        let image = std::env::var("FLY_IMAGE_REF").ok();
-        let version = var("FLY_MACHINE_VERSION")?;
-        let memory = std::env::var("FLY_VM_MEMORY_MB")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok());
-
-        Ok(Self {
-            id,
-            image,
-            version,
-            memory,
-        })
+        Config::from_env().machine().ok_or(Error::Unavailable)
+    }
+}
+
+/// The name of a Fly.io [process group][], like `app` or `worker`.
+///
+/// Process groups are defined by each app, so `ProcessGroup` is a thin wrapper
+/// around their name, which compares directly with strings.
+///
+/// [process group]: https://fly.io/docs/apps/processes/
+///
+/// ```
+/// use flytrap::ProcessGroup;
+///
+/// let group = ProcessGroup::from("worker");
+///
+/// assert_eq!(group, "worker");
+/// assert_ne!(group, "Worker");
+/// assert_eq!(group.to_string(), "worker");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct ProcessGroup(String);
+
+impl ProcessGroup {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for ProcessGroup {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for ProcessGroup {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ProcessGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<String> for ProcessGroup {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for ProcessGroup {
+    fn from(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+}
+
+impl PartialEq<str> for ProcessGroup {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ProcessGroup {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// The ID of a [Fly.io Machine][machine], like `148e21dad76789`.
+///
+/// Machine IDs are 14-digit hexadecimal strings; `MachineId` stores them as a
+/// `u64`.
+///
+/// [machine]: https://fly.io/docs/machines/
+///
+/// ```
+/// use flytrap::MachineId;
+///
+/// let id: MachineId = "148e21dad76789".parse().unwrap();
+///
+/// assert_eq!(id.as_u64(), 0x148e21dad76789);
+/// assert_eq!(id.to_string(), "148e21dad76789");
+/// assert!("b8d1a5b2-6a0c-4e0f".parse::<MachineId>().is_err());
+/// assert!("abc".parse::<MachineId>().is_err());
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub struct MachineId(u64);
+
+impl MachineId {
+    /// The number of hexadecimal digits in a machine ID.
+    pub const LENGTH: usize = 14;
+
+    pub const fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl FromStr for MachineId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != Self::LENGTH || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(Error::Parse);
+        }
+
+        u64::from_str_radix(s, 16)
+            .map(Self)
+            .map_err(|_| Error::Parse)
+    }
+}
+
+impl fmt::Display for MachineId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:014x}", self.0)
+    }
+}
+
+impl From<MachineId> for u64 {
+    fn from(value: MachineId) -> Self {
+        value.0
     }
 }
 
@@ -163,8 +369,7 @@ impl Machine {
 #[cfg(feature = "environment")]
 #[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
 pub fn hosted() -> bool {
-    use std::env::var;
-    matches!((var("FLY_APP_NAME"), var("FLY_PRIVATE_IP")), (Ok(_), Ok(_)))
+    crate::Config::from_env().hosted()
 }
 
 /// Read the [`$FLY_PRIVATE_IP`][private-ip] [environment variable][std::env::var],
@@ -192,33 +397,16 @@ pub fn private_address() -> Option<Ipv6Addr> {
     ip
 }
 
-/// Read the [`$FLY_PUBLIC_IP`][ip] [environment variable][std::env::var], if
-/// set to a valid IPv6 address.
-///
-/// [ip]: https://fly.io/docs/reference/services/#ipv6
-#[cfg(feature = "environment")]
-#[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
-pub fn public_address() -> Option<Ipv6Addr> {
-    std::env::var("FLY_PUBLIC_IP")
-        .ok()
-        .and_then(|value| value.parse::<Ipv6Addr>().ok())
-}
-
 /// Read the `$FLY_PRIVATE_IP` environment variable, if set to a valid
 /// IPv6 address.
 #[cfg(feature = "environment")]
 fn environment_address() -> Option<Ipv6Addr> {
-    let ip = std::env::var("FLY_PRIVATE_IP").ok();
-
-    match ip {
-        Some(ip) if !ip.is_empty() => ip.parse::<Ipv6Addr>().ok(),
-        _ => None,
-    }
+    crate::Config::from_env().private_ip
 }
 
 /// Find the first host IPv6 address starting with `fdaa:`.
 #[cfg(feature = "detect")]
-fn detect_address() -> Option<Ipv6Addr> {
+pub(crate) fn detect_address() -> Option<Ipv6Addr> {
     let interfaces = if_addrs::get_if_addrs().ok()?;
 
     interfaces
@@ -230,10 +418,240 @@ fn detect_address() -> Option<Ipv6Addr> {
         .next()
 }
 
-#[cfg(feature = "environment")]
-fn var(name: &'static str) -> Result<String, Error> {
-    match std::env::var(name) {
-        Ok(value) => Ok(value),
-        _ => Err(Error::Unavailable),
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{Machine, MachineId, Placement};
+    use crate::Error;
+
+    #[test]
+    fn from_env() {
+        let vars = HashMap::from([
+            ("FLY_APP_NAME", "flytrap"),
+            ("FLY_PROCESS_GROUP", "app"),
+            ("FLY_PUBLIC_IP", "2605:4c40:95:dfa1:0:dc2c:88b9:1"),
+            ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+            ("FLY_ALLOC_ID", "148e21dad76789"),
+            ("FLY_MACHINE_ID", "148e21dad76789"),
+            (
+                "FLY_IMAGE_REF",
+                "registry.fly.io/flytrap:deployment-01HF8P5F",
+            ),
+            ("FLY_MACHINE_VERSION", "01HF8P5FVXAA8K5QWWYJ8JBVBE"),
+            ("FLY_VM_MEMORY_MB", "256"),
+            ("FLY_REGION", "sea"),
+        ]);
+        let lookup = |name: &str| vars.get(name).map(|value| value.to_string());
+
+        let placement = Placement::from_env(lookup).unwrap();
+
+        assert_eq!(
+            placement,
+            Placement {
+                app: "flytrap".to_owned(),
+                process_group: Some("app".into()),
+                public_ip: Some("2605:4c40:95:dfa1:0:dc2c:88b9:1".parse().unwrap()),
+                private_ip: "fdaa:0:18:a7b:d6b:0:a:2".parse().unwrap(),
+                allocation: "148e21dad76789".to_owned(),
+                machine: Some(Machine {
+                    id: "148e21dad76789".to_owned(),
+                    image: Some("registry.fly.io/flytrap:deployment-01HF8P5F".to_owned()),
+                    version: "01HF8P5FVXAA8K5QWWYJ8JBVBE".to_owned(),
+                    memory: Some(256),
+                }),
+                location: "sea".parse().unwrap(),
+            }
+        );
+        #[cfg(feature = "regions")]
+        assert_eq!(Some(crate::Region::Seattle), placement.region());
+
+        let partial = |name: &str| match name {
+            "FLY_REGION" => None,
+            name => lookup(name),
+        };
+        assert!(matches!(
+            Placement::from_env(partial),
+            Err(Error::Unavailable)
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "api")]
+    async fn public_ipv4() {
+        use crate::mock::{HttpServer, Response};
+
+        let server = HttpServer::start(vec![
+            Response::new(
+                200,
+                r#"{"ips": [
+                    {"ip": "2a09:8280:1::1:abcd", "region": "global", "shared": false},
+                    {"ip": "66.241.124.1", "region": "global", "shared": true},
+                    {"ip": "137.66.1.2", "region": "global", "shared": false}
+                ]}"#,
+            ),
+            Response::new(200, r#"{"ips": []}"#),
+        ])
+        .await;
+        let client = server.client();
+        let placement = Placement::from_env(|name| {
+            match name {
+                "FLY_APP_NAME" => Some("flytrap"),
+                "FLY_PRIVATE_IP" => Some("fdaa:0:18:a7b:d6b:0:a:2"),
+                "FLY_ALLOC_ID" => Some("148e21dad76789"),
+                "FLY_REGION" => Some("sea"),
+                _ => None,
+            }
+            .map(ToOwned::to_owned)
+        })
+        .unwrap();
+
+        assert_eq!(
+            Some("137.66.1.2".parse().unwrap()),
+            placement.public_ipv4(&client).await.unwrap()
+        );
+        assert_eq!(None, placement.public_ipv4(&client).await.unwrap());
+        assert_eq!(
+            vec!["GET /v1/apps/flytrap/ip_assignments HTTP/1.1"; 2],
+            server.requests()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let vars = HashMap::from([
+            ("FLY_APP_NAME", "flytrap"),
+            ("FLY_PROCESS_GROUP", "app"),
+            ("FLY_PUBLIC_IP", "2605:4c40:95:dfa1:0:dc2c:88b9:1"),
+            ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+            ("FLY_ALLOC_ID", "148e21dad76789"),
+            ("FLY_MACHINE_ID", "148e21dad76789"),
+            ("FLY_MACHINE_VERSION", "01HF8P5FVXAA8K5QWWYJ8JBVBE"),
+            ("FLY_VM_MEMORY_MB", "256"),
+            ("FLY_REGION", "sea"),
+        ]);
+
+        for region in ["sea", "cai"] {
+            let lookup = |name: &str| match name {
+                "FLY_REGION" => Some(region.to_owned()),
+                name => vars.get(name).map(|value| value.to_string()),
+            };
+            let placement = Placement::from_env(lookup).unwrap();
+
+            let json = serde_json::to_string(&placement).unwrap();
+            assert!(
+                json.contains(&format!(r#""location":"{region}""#)),
+                "{json}"
+            );
+            assert!(json.contains(r#""process_group":"app""#), "{json}");
+            assert_eq!(placement, serde_json::from_str(&json).unwrap());
+        }
+    }
+
+    #[test]
+    fn process_group() {
+        let vars = HashMap::from([
+            ("FLY_APP_NAME", "flytrap"),
+            ("FLY_PROCESS_GROUP", "worker"),
+            ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+            ("FLY_ALLOC_ID", "148e21dad76789"),
+            ("FLY_REGION", "sea"),
+        ]);
+        let lookup = |name: &str| vars.get(name).map(|value| value.to_string());
+        let placement = Placement::from_env(lookup).unwrap();
+
+        assert!(placement.in_process_group("worker"));
+        assert!(!placement.in_process_group("Worker"));
+        assert!(!placement.in_process_group("app"));
+        assert_eq!(Some("worker"), placement.process_group.as_deref());
+
+        let group = placement.group().unwrap();
+        assert_eq!(group, "worker");
+        assert_eq!(group, *"worker");
+        assert_eq!("worker", group.as_ref());
+        assert_eq!("worker", group.to_string());
+
+        let ungrouped = Placement {
+            process_group: None,
+            ..placement
+        };
+        assert!(!ungrouped.in_process_group("worker"));
+        assert!(!ungrouped.in_process_group(""));
+        assert_eq!(None, ungrouped.group());
+    }
+
+    #[test]
+    fn machine_id() {
+        let vars = HashMap::from([
+            ("FLY_APP_NAME", "flytrap"),
+            ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+            ("FLY_ALLOC_ID", "148e21dad76789"),
+            ("FLY_REGION", "sea"),
+        ]);
+        let lookup = |name: &str| vars.get(name).map(|value| value.to_string());
+        let placement = Placement::from_env(lookup).unwrap();
+
+        assert_eq!(
+            Some(MachineId::new(0x148e21dad76789)),
+            placement.machine_id()
+        );
+
+        let legacy = Placement {
+            allocation: "b8d1a5b2-6a0c-4e0f-9d6b-5c1f1f2b1a3e".to_owned(),
+            ..placement.clone()
+        };
+        assert_eq!(None, legacy.machine_id());
+
+        let machine = Placement {
+            machine: Some(Machine {
+                id: "4d89699c030518".to_owned(),
+                image: None,
+                version: "01HF8P5FVXAA8K5QWWYJ8JBVBE".to_owned(),
+                memory: None,
+            }),
+            ..legacy
+        };
+        assert_eq!("4d89699c030518", machine.machine_id().unwrap().to_string());
+
+        assert!("".parse::<MachineId>().is_err());
+        assert!("+148e21dad76789".parse::<MachineId>().is_err());
+        assert!("148e21dad76789148e21".parse::<MachineId>().is_err());
+        assert!("abc".parse::<MachineId>().is_err());
+        assert!("0000000000000abc".parse::<MachineId>().is_err());
+        assert_eq!(
+            "00000000000abc",
+            "00000000000abc".parse::<MachineId>().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regions")]
+    fn compare() {
+        use crate::Region;
+
+        let placement = Placement {
+            app: "flytrap".to_owned(),
+            process_group: Some("app".into()),
+            public_ip: None,
+            private_ip: "fdaa:0:18:a7b:d6b:0:a:2".parse().unwrap(),
+            allocation: "148e21dad76789".to_owned(),
+            machine: Some(Machine {
+                id: "148e21dad76789".to_owned(),
+                image: None,
+                version: "01HF8P5FVXAA8K5QWWYJ8JBVBE".to_owned(),
+                memory: Some(256),
+            }),
+            location: Region::Seattle.into(),
+        };
+
+        let migrated = Placement {
+            location: Region::Ashburn.into(),
+            ..placement.clone()
+        };
+
+        assert_eq!(placement, placement.clone());
+        assert_ne!(placement, migrated);
+        assert_eq!(placement.machine, migrated.machine);
     }
 }
@@ -1,4 +1,4 @@
-use std::{fmt, ops::Deref, str::FromStr};
+use std::{collections::BTreeMap, fmt, ops::Deref, str::FromStr};
 
 use enum_map::{enum_map, Enum, EnumMap};
 use geo_types::Point;
@@ -16,7 +16,7 @@ use noisy_float::types::R32;
 ///
 /// let loc: Location = Region::Santiago.into();
 /// ```
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(untagged))]
 pub enum Location {
@@ -35,6 +35,23 @@ impl Location {
         }
     }
 
+    /// The three-letter [code][RegionCode] for this location, whether or not
+    /// its region is known.
+    ///
+    /// ```
+    /// use flytrap::{Location, Region};
+    ///
+    /// let location = Location::from(Region::Tokyo);
+    /// assert_eq!(location.code().to_string(), "nrt");
+    /// ```
+    #[inline]
+    pub const fn code(&self) -> RegionCode {
+        match *self {
+            Location::Region(region) => RegionCode((region as u32).to_be_bytes()),
+            Location::Unknown(code) => code,
+        }
+    }
+
     #[inline]
     fn key(&self) -> RegionKey<'_> {
         match self {
@@ -120,7 +137,7 @@ impl From<Location> for Option<Region> {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Enum, PartialEq, Eq, Copy, Clone, Debug)]
+#[derive(Enum, PartialEq, Eq, Hash, Copy, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[repr(u32)]
 pub enum Region {
@@ -245,8 +262,203 @@ impl Region {
         DETAILS.iter().map(|(r, d)| (r, *d))
     }
 
+    /// The Fly.io features which can be used by machines placed in this
+    /// region.
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// assert!(Region::Chicago.capabilities().gpu);
+    /// assert!(!Region::Bogota.capabilities().gpu);
+    /// ```
+    pub const fn capabilities(&self) -> Capabilities {
+        use Region::*;
+
+        match self {
+            Amsterdam | Ashburn | Chicago | SanJose | Sydney => Capabilities::GPU,
+            _ => Capabilities::STANDARD,
+        }
+    }
+
+    /// The [continent][Continent] where this region is located.
+    ///
+    /// ```
+    /// use flytrap::{Continent, Region};
+    ///
+    /// assert_eq!(Region::Johannesburg.continent(), Continent::Africa);
+    /// assert_eq!(Region::Queretaro.continent(), Continent::NorthAmerica);
+    /// ```
+    pub const fn continent(&self) -> Continent {
+        use Region::*;
+
+        match self {
+            Johannesburg => Continent::Africa,
+            Chennai | HongKong | Mumbai | Singapore | Tokyo => Continent::Asia,
+            Amsterdam | Bucharest | Frankfurt | London | Madrid | Paris | Stockholm | Warsaw => {
+                Continent::Europe
+            }
+            Ashburn | Atlanta | Boston | Chicago | Dallas | Denver | Guadalajara | LosAngeles
+            | Miami | Montreal | Phoenix | Queretaro | SanJose | Seattle | Secaucus | Toronto => {
+                Continent::NorthAmerica
+            }
+            Sydney => Continent::Oceania,
+            Bogota | Ezeiza | RioDeJaneiro | Santiago | SaoPaulo => Continent::SouthAmerica,
+        }
+    }
+
+    /// The [IANA time zone][tz] identifier for the region's local time, like
+    /// `America/Chicago`.
+    ///
+    /// Resolving the identifier to a UTC offset is left to a time zone
+    /// database crate, like [`chrono-tz`](https://lib.rs/crates/chrono-tz).
+    ///
+    /// [tz]: https://www.iana.org/time-zones
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// assert_eq!(Region::Tokyo.timezone(), "Asia/Tokyo");
+    /// ```
+    pub const fn timezone(&self) -> &'static str {
+        use Region::*;
+
+        match self {
+            Johannesburg => "Africa/Johannesburg",
+            Ezeiza => "America/Argentina/Buenos_Aires",
+            Bogota => "America/Bogota",
+            Chicago | Dallas => "America/Chicago",
+            Denver => "America/Denver",
+            LosAngeles | SanJose | Seattle => "America/Los_Angeles",
+            Guadalajara | Queretaro => "America/Mexico_City",
+            Ashburn | Atlanta | Boston | Miami | Secaucus => "America/New_York",
+            Phoenix => "America/Phoenix",
+            Santiago => "America/Santiago",
+            RioDeJaneiro | SaoPaulo => "America/Sao_Paulo",
+            Montreal | Toronto => "America/Toronto",
+            HongKong => "Asia/Hong_Kong",
+            Chennai | Mumbai => "Asia/Kolkata",
+            Singapore => "Asia/Singapore",
+            Tokyo => "Asia/Tokyo",
+            Sydney => "Australia/Sydney",
+            Amsterdam => "Europe/Amsterdam",
+            Frankfurt => "Europe/Berlin",
+            Bucharest => "Europe/Bucharest",
+            London => "Europe/London",
+            Madrid => "Europe/Madrid",
+            Paris => "Europe/Paris",
+            Stockholm => "Europe/Stockholm",
+            Warsaw => "Europe/Warsaw",
+        }
+    }
+
+    /// The name of the metropolitan area served by this region.
+    ///
+    /// Usually the same as the [city][City] name, but regions hosted in a
+    /// suburb or at an airport are grouped with the city they serve, so that
+    /// regions in the same metro area can be treated as one location.
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// assert_eq!(Region::Secaucus.metro(), "New York");
+    /// assert_eq!(Region::Chicago.metro(), "Chicago");
+    /// ```
+    pub fn metro(&self) -> &'static str {
+        match self {
+            Region::Ashburn => "Washington, D.C.",
+            Region::Ezeiza => "Buenos Aires",
+            Region::SanJose => "San Francisco Bay Area",
+            Region::Secaucus => "New York",
+            _ => self.city.name,
+        }
+    }
+
+    /// The great-circle distance between this region and `other`, in
+    /// kilometers.
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// let km = Region::Paris.distance_to(Region::London);
+    /// assert!((300.0..400.0).contains(&km));
+    /// ```
+    pub fn distance_to(&self, other: Region) -> f64 {
+        self.details().distance_to(&other.details())
+    }
+
+    /// Find the region closest to the given latitude and longitude.
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// // Wichita, Kansas
+    /// assert_eq!(Region::nearest(37.69, -97.34), Region::Dallas);
+    /// ```
+    pub fn nearest(lat: f32, lon: f32) -> Region {
+        let geo = point(lat, lon);
+
+        DETAILS
+            .iter()
+            .map(|(region, details)| (region, haversine(geo, details.city.geo)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(region, _)| region)
+            .expect("no known Fly.io regions")
+    }
+
+    /// Find the `n` regions closest to the given latitude and longitude,
+    /// nearest first.
+    pub fn nearest_n(lat: f32, lon: f32, n: usize) -> Vec<Region> {
+        let geo = point(lat, lon);
+
+        let mut regions: Vec<(Region, f64)> = DETAILS
+            .iter()
+            .map(|(region, details)| (region, haversine(geo, details.city.geo)))
+            .collect();
+        regions.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        regions
+            .into_iter()
+            .take(n)
+            .map(|(region, _)| region)
+            .collect()
+    }
+
+    /// Find the other regions within `max_km` kilometers of this one, nearest
+    /// first.
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// let neighbors = Region::Paris.neighbors(500.0);
+    /// assert_eq!(neighbors[0], Region::London);
+    /// ```
+    pub fn neighbors(&self, max_km: f64) -> Vec<Region> {
+        let mut regions: Vec<(Region, f64)> = Region::all()
+            .filter(|(region, _)| region != self)
+            .map(|(region, _)| (region, self.distance_to(region)))
+            .filter(|(_, km)| *km <= max_km)
+            .collect();
+        regions.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        regions.into_iter().map(|(region, _)| region).collect()
+    }
+
+    /// The region's packed `u32` representation: its three-letter code as
+    /// big-endian ASCII bytes, followed by a zero byte.
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// assert_eq!(Region::Chicago.as_u32(), 0x6f726400);
+    /// assert_eq!(Region::try_from(0x6f726400), Ok(Region::Chicago));
+    /// ```
+    #[inline]
+    pub const fn as_u32(&self) -> u32 {
+        *self as u32
+    }
+
     fn key(&self) -> RegionKey<'_> {
-        (self.city.geo.x(), self.city.geo.y(), &self.code)
+        (self.city.geo.x(), self.city.geo.y(), self.code)
     }
 }
 
@@ -265,6 +477,12 @@ impl PartialOrd for Region {
     }
 }
 
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
 impl Deref for Region {
     type Target = RegionDetails<'static>;
 
@@ -282,8 +500,67 @@ impl fmt::Display for Region {
 impl FromStr for Region {
     type Err = RegionError;
 
+    /// Parse a [`Region`] from its code, ignoring ASCII case.
+    ///
+    /// To look up a region by its city or full name, use [`Region::from_name`].
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// assert_eq!("ord".parse(), Ok(Region::Chicago));
+    /// assert_eq!("ORD".parse(), Ok(Region::Chicago));
+    /// assert!("chicago".parse::<Region>().is_err());
+    /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        if let Ok(region) = Self::from_code(s) {
+            return Ok(region);
+        }
+
+        match s.as_bytes() {
+            &[a, b, c] => {
+                let code = [
+                    a.to_ascii_lowercase(),
+                    b.to_ascii_lowercase(),
+                    c.to_ascii_lowercase(),
+                ];
+                let code = std::str::from_utf8(&code).map_err(|_| RegionError::Unrecognized)?;
+                Self::from_code(code)
+            }
+            _ => Err(RegionError::Unrecognized),
+        }
+    }
+}
+
+impl Region {
+    /// Look up a [`Region`] by its name, ignoring case.
+    ///
+    /// The input is matched against [city][City] names (`chicago`) first, and
+    /// then against full region names (`Chicago, Illinois (US)`). No two
+    /// regions currently share a city name; if that changes, the first
+    /// matching [`Region`] in declaration order wins.
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// assert_eq!(Region::from_name("chicago"), Ok(Region::Chicago));
+    /// assert_eq!(Region::from_name("Chicago, Illinois (US)"), Ok(Region::Chicago));
+    /// ```
+    pub fn from_name(name: &str) -> Result<Self, RegionError> {
+        DETAILS
+            .iter()
+            .find(|(_, details)| eq_ignore_case(details.city.name, name))
+            .or_else(|| {
+                DETAILS
+                    .iter()
+                    .find(|(_, details)| eq_ignore_case(details.name, name))
+            })
+            .map(|(region, _)| region)
+            .ok_or(RegionError::Unrecognized)
+    }
+
+    /// Look up a [`Region`] by its exact (lowercase) code.
+    fn from_code(code: &str) -> Result<Self, RegionError> {
+        match code {
             "ams" => Ok(Self::Amsterdam),
             "arn" => Ok(Self::Stockholm),
             "atl" => Ok(Self::Atlanta),
@@ -325,6 +602,182 @@ impl FromStr for Region {
     }
 }
 
+impl From<Region> for u32 {
+    fn from(value: Region) -> Self {
+        value.as_u32()
+    }
+}
+
+impl TryFrom<u32> for Region {
+    type Error = RegionError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value.to_be_bytes() {
+            [a, b, c, 0] => Self::from_code(
+                std::str::from_utf8(&[a, b, c]).map_err(|_| RegionError::Unrecognized)?,
+            ),
+            _ => Err(RegionError::Unrecognized),
+        }
+    }
+}
+
+/// Group every known [`Region`] by the [country][RegionDetails::country]
+/// where it's located.
+///
+/// Each country's regions are listed in their geographic [order][Ord].
+///
+/// ```
+/// use flytrap::{regions_by_country, Region};
+///
+/// let countries = regions_by_country();
+/// assert_eq!(countries["CA"], [Region::Toronto, Region::Montreal]);
+/// ```
+pub fn regions_by_country() -> BTreeMap<&'static str, Vec<Region>> {
+    let mut countries: BTreeMap<&'static str, Vec<Region>> = BTreeMap::new();
+
+    for (region, details) in Region::all() {
+        countries.entry(details.country()).or_default().push(region);
+    }
+    for regions in countries.values_mut() {
+        regions.sort();
+    }
+
+    countries
+}
+
+/// A compact set of [regions][Region], stored as a bitset.
+///
+/// ```
+/// use flytrap::{Region, RegionSet};
+///
+/// let deployed: RegionSet = [Region::Chicago, Region::Amsterdam].into_iter().collect();
+/// let preferred: RegionSet = [Region::Amsterdam, Region::Tokyo].into_iter().collect();
+///
+/// assert!(deployed.contains(Region::Chicago));
+/// assert_eq!(
+///     deployed.intersection(preferred).iter().collect::<Vec<_>>(),
+///     [Region::Amsterdam]
+/// );
+/// ```
+#[derive(PartialEq, Eq, Hash, Default, Copy, Clone)]
+pub struct RegionSet(u64);
+
+const _: () = assert!(<Region as Enum>::LENGTH <= u64::BITS as usize);
+
+impl RegionSet {
+    /// An empty set.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// A set of every known region.
+    pub fn all() -> Self {
+        Region::all().map(|(region, _)| region).collect()
+    }
+
+    /// Add a region to the set, returning `true` if it wasn't already present.
+    pub fn insert(&mut self, region: Region) -> bool {
+        let present = self.contains(region);
+        self.0 |= bit(region);
+        !present
+    }
+
+    /// Remove a region from the set, returning `true` if it was present.
+    pub fn remove(&mut self, region: Region) -> bool {
+        let present = self.contains(region);
+        self.0 &= !bit(region);
+        present
+    }
+
+    /// Check if the set includes a region.
+    pub fn contains(&self, region: Region) -> bool {
+        self.0 & bit(region) != 0
+    }
+
+    /// The number of regions in the set.
+    pub const fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Check if the set has no regions.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The regions in either this set or `other`.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// The regions in both this set and `other`.
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// The regions in this set but not in `other`.
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Iterate over the regions in the set, in the same order as
+    /// [`Region::all`].
+    pub fn iter(&self) -> impl Iterator<Item = Region> {
+        let bits = self.0;
+
+        (0..<Region as Enum>::LENGTH)
+            .filter(move |i| bits & (1 << i) != 0)
+            .map(Region::from_usize)
+    }
+}
+
+impl FromIterator<Region> for RegionSet {
+    fn from_iter<I: IntoIterator<Item = Region>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Region> for RegionSet {
+    fn extend<I: IntoIterator<Item = Region>>(&mut self, iter: I) {
+        for region in iter {
+            self.insert(region);
+        }
+    }
+}
+
+impl From<Region> for RegionSet {
+    fn from(value: Region) -> Self {
+        Self(bit(value))
+    }
+}
+
+impl fmt::Debug for RegionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RegionSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RegionSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let regions = Vec::<Region>::deserialize(deserializer)?;
+        Ok(regions.into_iter().collect())
+    }
+}
+
+#[inline]
+fn bit(region: Region) -> u64 {
+    1 << region.into_usize()
+}
+
 /// Attributes of a known [`Region`].
 ///
 /// ```
@@ -343,6 +796,73 @@ pub struct RegionDetails<'l> {
     pub city: City<'l>,
 }
 
+impl<'l> RegionDetails<'l> {
+    /// The [ISO 3166-1 alpha-2][iso] code of the country where this region is
+    /// located; shorthand for `city.country`.
+    ///
+    /// [iso]: https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// assert_eq!(Region::Stockholm.country(), "SE");
+    /// ```
+    #[inline]
+    pub const fn country(&self) -> &'l str {
+        self.city.country
+    }
+
+    /// The great-circle distance between this region's [city][City] and
+    /// `other`'s, in kilometers.
+    pub fn distance_to(&self, other: &RegionDetails<'_>) -> f64 {
+        haversine(self.city.geo, other.city.geo)
+    }
+
+    /// The latitude of the region's [city][City], in degrees north; the `y`
+    /// coordinate of `city.geo`.
+    #[inline]
+    pub fn latitude(&self) -> f64 {
+        self.city.geo.y().raw().into()
+    }
+
+    /// The longitude of the region's [city][City], in degrees east; the `x`
+    /// coordinate of `city.geo`.
+    #[inline]
+    pub fn longitude(&self) -> f64 {
+        self.city.geo.x().raw().into()
+    }
+
+    /// The coordinates of the region's [city][City], as `(latitude,
+    /// longitude)`.
+    ///
+    /// Note that this is the reverse of the `(x, y)` order of `city.geo`.
+    ///
+    /// ```
+    /// use flytrap::Region;
+    ///
+    /// let (lat, lon) = Region::Sydney.coordinates();
+    /// assert!(lat < 0.0 && lon > 0.0);
+    /// ```
+    #[inline]
+    pub fn coordinates(&self) -> (f64, f64) {
+        (self.latitude(), self.longitude())
+    }
+}
+
+impl From<Region> for Point<R32> {
+    fn from(value: Region) -> Self {
+        value.city.geo
+    }
+}
+
+/// The region's [`coordinates`][RegionDetails::coordinates], as `(latitude,
+/// longitude)`.
+impl From<&RegionDetails<'_>> for (f64, f64) {
+    fn from(value: &RegionDetails<'_>) -> Self {
+        value.coordinates()
+    }
+}
+
 impl RegionDetails<'static> {
     pub(crate) const fn new(
         code: &'static str,
@@ -363,15 +883,128 @@ impl RegionDetails<'static> {
     }
 }
 
+/// The continent where a Fly.io [region][Region] is located.
+///
+/// Mexico and Central America are considered part of North America.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Continent {
+    Africa,
+    Asia,
+    Europe,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+
+impl Continent {
+    /// The English name of the continent.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Africa => "Africa",
+            Self::Asia => "Asia",
+            Self::Europe => "Europe",
+            Self::NorthAmerica => "North America",
+            Self::Oceania => "Oceania",
+            Self::SouthAmerica => "South America",
+        }
+    }
+}
+
+impl fmt::Display for Continent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A [`Region`] which serializes as its full [details][RegionDetails], rather
+/// than just its code.
+///
+/// ```
+/// # #[cfg(feature = "serde")]
+/// # fn main() -> Result<(), serde_json::Error> {
+/// use flytrap::{DetailedRegion, Region};
+///
+/// let json = serde_json::to_value(DetailedRegion(Region::Paris))?;
+/// assert_eq!(json["code"], "cdg");
+/// assert_eq!(json["city"]["name"], "Paris");
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "serde"))]
+/// # fn main() {}
+/// ```
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct DetailedRegion(pub Region);
+
+impl Deref for DetailedRegion {
+    type Target = Region;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Region> for DetailedRegion {
+    fn from(value: Region) -> Self {
+        Self(value)
+    }
+}
+
+impl From<DetailedRegion> for Region {
+    fn from(value: DetailedRegion) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DetailedRegion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.details().serialize(serializer)
+    }
+}
+
 /// Describes a city where a Fly.io [region][Region] is hosted.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct City<'l> {
     pub name: &'l str,
     pub country: &'l str,
+    /// The city's coordinates, with the longitude as `x` and the latitude as
+    /// `y` (following the [`geo`](https://lib.rs/crates/geo) convention).
     pub geo: Point<R32>,
 }
 
+/// Fly.io platform features which are only available in some [regions][Region].
+///
+/// This data is maintained by hand from the Fly.io [GPU][gpu] and
+/// [volume][volumes] documentation, and is not fetched from the Fly.io API
+/// with the rest of the region list. GPU availability changes more often than
+/// the set of regions does, so treat these flags as a placement hint rather
+/// than a guarantee.
+///
+/// [gpu]: https://fly.io/docs/gpus/
+/// [volumes]: https://fly.io/docs/volumes/
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Capabilities {
+    /// Machines with GPUs can be created in the region.
+    pub gpu: bool,
+    /// Volumes can be created in the region.
+    pub volumes: bool,
+}
+
+impl Capabilities {
+    const STANDARD: Self = Self {
+        gpu: false,
+        volumes: true,
+    };
+    const GPU: Self = Self {
+        gpu: true,
+        volumes: true,
+    };
+}
+
 lazy_static! {
     static ref DETAILS: EnumMap<Region, RegionDetails<'static>> = enum_map! {
         Region::Amsterdam => RegionDetails::new("ams", "Amsterdam, Netherlands", "Amsterdam", "NL", [52.374342, 4.895439]),
@@ -433,8 +1066,7 @@ lazy_static! {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub struct RegionCode([u8; 4]);
 
 impl RegionCode {
@@ -443,6 +1075,10 @@ impl RegionCode {
     /// [region]: https://fly.io/docs/reference/regions/
     pub const LENGTH: usize = 3;
 
+    /// A placeholder for a missing region code, like an empty `Fly-Region`
+    /// header. It displays as `???`, and is never produced by parsing.
+    pub const UNKNOWN: RegionCode = RegionCode([b'?', b'?', b'?', 0]);
+
     /// The geographic coordinates for "null island", a fictitious landform at
     /// 0º latitude and longitude. Used as the coordinates for a [`Location::Unknown`].
     pub const NULL_ISLAND: (R32, R32) = (R32::unchecked_new(0.0), R32::unchecked_new(0.0));
@@ -460,13 +1096,29 @@ impl RegionCode {
         input.len() == Self::LENGTH && input.chars().all(|c| c.is_ascii_lowercase())
     }
 
+    /// The region code, as a string like `ord`.
+    ///
+    /// Region codes are ASCII by construction, so this never fails; if that
+    /// invariant were somehow broken, `---` would be returned.
+    ///
+    /// ```
+    /// use flytrap::RegionCode;
+    ///
+    /// let code: RegionCode = "cai".parse().unwrap();
+    /// assert_eq!(code.as_str(), "cai");
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(self.as_slice()).unwrap_or("---")
+    }
+
     #[inline(always)]
     fn as_slice(&self) -> &[u8] {
         &self.0[..3]
     }
 
     fn key(&self) -> RegionKey<'_> {
-        (Self::NULL_ISLAND.0, Self::NULL_ISLAND.1, self.as_ref())
+        (Self::NULL_ISLAND.0, Self::NULL_ISLAND.1, self.as_str())
     }
 }
 
@@ -478,7 +1130,7 @@ impl AsRef<[u8]> for RegionCode {
 
 impl AsRef<str> for RegionCode {
     fn as_ref(&self) -> &str {
-        std::str::from_utf8(self.as_slice()).expect("invalid region code")
+        self.as_str()
     }
 }
 
@@ -497,10 +1149,7 @@ impl FromStr for RegionCode {
 
 impl fmt::Display for RegionCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match std::str::from_utf8(&self.0) {
-            Ok(code) => write!(f, "{code}"),
-            Err(_) => write!(f, "---"),
-        }
+        f.write_str(self.as_str())
     }
 }
 
@@ -522,6 +1171,23 @@ impl PartialOrd for RegionCode {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for RegionCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RegionCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let code = std::borrow::Cow::<'de, str>::deserialize(deserializer)?;
+        code.parse().map_err(D::Error::custom)
+    }
+}
+
 /// An error parsing a [`Region`] or [`RegionCode`].
 #[derive(thiserror::Error, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum RegionError {
@@ -531,6 +1197,23 @@ pub enum RegionError {
     Unrecognized,
 }
 
+/// The mean radius of the Earth, in kilometers.
+const EARTH_RADIUS: f64 = 6371.0088;
+
+/// The great-circle distance between two `(lon, lat)` points, in kilometers.
+fn haversine(a: Point<R32>, b: Point<R32>) -> f64 {
+    let (lat1, lon1) = (f64::from(a.y().raw()), f64::from(a.x().raw()));
+    let (lat2, lon2) = (f64::from(b.y().raw()), f64::from(b.x().raw()));
+
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lon = (lon2 - lon1).to_radians();
+
+    let h = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS * h.sqrt().asin()
+}
+
 #[inline(always)]
 const fn point(lat: f32, lon: f32) -> Point<R32> {
     Point(geo_types::Coord {
@@ -541,7 +1224,9 @@ const fn point(lat: f32, lon: f32) -> Point<R32> {
 
 #[cfg(test)]
 mod test {
-    use super::{City, Location, Region, RegionCode, RegionError};
+    use super::{
+        Capabilities, City, Continent, Location, Region, RegionCode, RegionError, RegionSet,
+    };
 
     #[test]
     fn parse() {
@@ -556,6 +1241,36 @@ mod test {
         ));
     }
 
+    #[test]
+    fn parse_names() {
+        assert_eq!(Ok(Region::Chicago), "ORD".parse());
+        assert_eq!(Ok(Region::Chicago), "Ord".parse());
+        assert_eq!(Ok(Region::Chicago), Region::from_name("chicago"));
+        assert_eq!(
+            Ok(Region::Chicago),
+            Region::from_name("Chicago, Illinois (US)")
+        );
+        assert_eq!(Ok(Region::Bogota), Region::from_name("BOGOTÁ"));
+        assert_eq!(
+            Ok(Region::RioDeJaneiro),
+            Region::from_name("Rio de Janeiro")
+        );
+        assert_eq!(Ok(Location::Region(Region::Tokyo)), "nrt".parse());
+        assert_eq!(Ok(Location::Region(Region::Tokyo)), "NRT".parse());
+
+        assert_eq!(Err(RegionError::Unrecognized), "cai".parse::<Region>());
+        assert_eq!(Err(RegionError::Unrecognized), "chicago".parse::<Region>());
+        assert_eq!(Err(RegionError::Unrecognized), Region::from_name("Cairo"));
+        assert_eq!(Err(RegionError::Invalid), "tokyo".parse::<Location>());
+        assert_eq!(Err(RegionError::Unrecognized), Region::try_from(0x4f524400));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("bog", Location::Region(Region::Bogota).to_string());
+        assert_eq!("oak", "oak".parse::<RegionCode>().unwrap().to_string());
+    }
+
     #[test]
     fn region_details() {
         let ord = Region::Chicago;
@@ -608,4 +1323,367 @@ mod test {
         assert!(Region::all().count() >= 30);
         assert!(Region::all().all(|(r, d)| r.details() == d));
     }
+
+    #[test]
+    fn capabilities() {
+        use Region::*;
+
+        for region in [Amsterdam, Ashburn, Chicago, SanJose, Sydney] {
+            assert_eq!(
+                Capabilities {
+                    gpu: true,
+                    volumes: true
+                },
+                region.capabilities(),
+                "{region}"
+            );
+        }
+
+        assert!(!Madrid.capabilities().gpu);
+        assert!(Region::all().all(|(r, _)| r.capabilities().volumes));
+    }
+
+    #[test]
+    fn distance() {
+        let km = Region::Chicago.distance_to(Region::Ashburn);
+        assert!((890.0..940.0).contains(&km), "ord → iad: {km} km");
+
+        assert_eq!(0.0, Region::Tokyo.distance_to(Region::Tokyo));
+        assert_eq!(
+            Region::Sydney.distance_to(Region::Santiago),
+            Region::Santiago.distance_to(Region::Sydney)
+        );
+        assert_eq!(
+            Region::Chicago.distance_to(Region::Ashburn),
+            Region::Chicago
+                .details()
+                .distance_to(&Region::Ashburn.details())
+        );
+    }
+
+    #[test]
+    fn nearest() {
+        use Region::*;
+
+        // Kansas City
+        assert_eq!(Chicago, Region::nearest(39.1, -94.6));
+        assert_eq!(vec![Chicago, Dallas], Region::nearest_n(39.1, -94.6, 2));
+
+        // East of Tokyo, just short of the antimeridian
+        assert_eq!(Tokyo, Region::nearest(35.6, 179.0));
+        // Fiji, just past it
+        assert_eq!(Sydney, Region::nearest(-17.7, -178.0));
+
+        assert_eq!(
+            Region::all().count(),
+            Region::nearest_n(0.0, 0.0, 100).len()
+        );
+        assert!(Region::nearest_n(0.0, 0.0, 0).is_empty());
+    }
+
+    #[test]
+    fn metro() {
+        use Region::*;
+
+        // No two current regions share a metro, so this only checks that the
+        // grouped regions map to the city they serve, and that any regions
+        // which do share a metro are close together.
+        assert_eq!("New York", Secaucus.metro());
+        assert_eq!("Buenos Aires", Ezeiza.metro());
+        assert_ne!(Secaucus.metro(), Ashburn.metro());
+        assert_ne!(Secaucus.metro(), Boston.metro());
+
+        for (a, _) in Region::all() {
+            for (b, _) in Region::all() {
+                if a.metro() == b.metro() {
+                    assert!(a.distance_to(b) < 100.0, "{a} and {b} share a metro");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn continent() {
+        use Region::*;
+
+        for region in [Secaucus, Ashburn, Toronto, Guadalajara] {
+            assert_eq!(Continent::NorthAmerica, region.continent(), "{region}");
+        }
+        for region in [Bogota, Ezeiza, SaoPaulo, Santiago] {
+            assert_eq!(Continent::SouthAmerica, region.continent(), "{region}");
+        }
+        assert_eq!(Continent::Africa, Johannesburg.continent());
+        assert_eq!(Continent::Oceania, Sydney.continent());
+        assert_eq!(Continent::Asia, Singapore.continent());
+        assert_eq!(Continent::Europe, London.continent());
+
+        assert_eq!("North America", Continent::NorthAmerica.to_string());
+    }
+
+    #[test]
+    fn packed() {
+        assert_eq!(Ok(Region::Chicago), Region::try_from(0x6f726400));
+        assert_eq!(0x6f726400, u32::from(Region::Chicago));
+        assert!(Region::all().all(|(r, _)| Region::try_from(r.as_u32()) == Ok(r)));
+
+        assert_eq!(Err(RegionError::Unrecognized), Region::try_from(0x6f726401));
+        assert_eq!(Err(RegionError::Unrecognized), Region::try_from(0x63616900));
+        assert_eq!(Err(RegionError::Unrecognized), Region::try_from(0xffffff00));
+        assert_eq!(Err(RegionError::Unrecognized), Region::try_from(0));
+    }
+
+    #[test]
+    fn timezone() {
+        assert_eq!("Asia/Tokyo", Region::Tokyo.timezone());
+        assert_eq!("America/Chicago", Region::Dallas.timezone());
+        assert_eq!("America/Phoenix", Region::Phoenix.timezone());
+
+        for (region, _) in Region::all() {
+            let (area, _) = region.timezone().split_once('/').unwrap();
+            let expected = match region.continent() {
+                Continent::Africa => "Africa",
+                Continent::Asia => "Asia",
+                Continent::Europe => "Europe",
+                Continent::NorthAmerica | Continent::SouthAmerica => "America",
+                Continent::Oceania => "Australia",
+            };
+
+            assert_eq!(expected, area, "{region}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_code() {
+        let code: RegionCode = "abc".parse().unwrap();
+        assert_eq!(r#""abc""#, serde_json::to_string(&code).unwrap());
+        assert_eq!(
+            code,
+            serde_json::from_str::<RegionCode>(r#""abc""#).unwrap()
+        );
+
+        let location = Location::Unknown(code);
+        let json = serde_json::to_string(&location).unwrap();
+        assert_eq!(r#""abc""#, json);
+        assert_eq!(location, serde_json::from_str::<Location>(&json).unwrap());
+        assert_eq!(
+            Location::Region(Region::Tokyo),
+            serde_json::from_str::<Location>(r#""nrt""#).unwrap()
+        );
+
+        assert!(serde_json::from_str::<RegionCode>(r#""ABCD""#).is_err());
+        assert!(serde_json::from_str::<RegionCode>("[97, 98, 99, 0]").is_err());
+    }
+
+    #[test]
+    fn countries() {
+        assert_eq!("US", Region::Chicago.country());
+        assert_eq!("JP", Region::Tokyo.country());
+
+        let countries = super::regions_by_country();
+        let us = &countries["US"];
+        assert!(us.contains(&Region::Chicago));
+        assert!(us.contains(&Region::Dallas));
+        assert!(us.len() >= 10);
+        assert!(us.windows(2).all(|w| w[0] < w[1]));
+
+        assert_eq!(
+            Region::all().count(),
+            countries.values().map(Vec::len).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn location_accessors() {
+        let known = Location::Region(Region::Chicago);
+        assert_eq!(Some(Region::Chicago), known.region());
+        assert_eq!(RegionCode::from(Region::Chicago), known.code());
+        assert_eq!("ord", known.code().to_string());
+
+        let unknown: Location = "cai".parse().unwrap();
+        assert_eq!(None, unknown.region());
+        assert_eq!("cai".parse::<RegionCode>().unwrap(), unknown.code());
+        assert_eq!("cai", unknown.code().to_string());
+
+        const CODE: RegionCode = Location::Region(Region::Paris).code();
+        assert_eq!("cdg", CODE.to_string());
+    }
+
+    #[test]
+    fn code_as_str() {
+        for input in ["ord", "cai", "xyz"] {
+            let code: RegionCode = input.parse().unwrap();
+            assert_eq!(input, code.as_str());
+            assert_eq!(code, code.as_str().parse().unwrap());
+        }
+
+        assert_eq!("ams", RegionCode::from(Region::Amsterdam).as_str());
+        assert_eq!("???", RegionCode::UNKNOWN.as_str());
+    }
+
+    #[test]
+    fn hash() {
+        use std::collections::HashMap;
+
+        let mut counts: HashMap<Location, usize> = HashMap::new();
+        for code in ["ord", "ams", "cai", "ord", "cai", "xyz", "ord"] {
+            *counts.entry(code.parse().unwrap()).or_default() += 1;
+        }
+
+        assert_eq!(4, counts.len());
+        assert_eq!(3, counts[&Location::Region(Region::Chicago)]);
+        assert_eq!(1, counts[&Region::Amsterdam.into()]);
+        assert_eq!(2, counts[&"cai".parse::<Location>().unwrap()]);
+        assert_eq!(
+            1,
+            counts[&Location::Unknown("xyz".parse::<RegionCode>().unwrap())]
+        );
+    }
+
+    #[test]
+    fn coordinates() {
+        // (region, latitude range, longitude range)
+        let bounds = [
+            (Region::Chicago, 24.0..50.0, -125.0..-66.0),
+            (Region::Seattle, 24.0..50.0, -125.0..-66.0),
+            (Region::Toronto, 41.0..84.0, -141.0..-52.0),
+            (Region::SaoPaulo, -34.0..6.0, -74.0..-34.0),
+            (Region::Santiago, -56.0..-17.0, -76.0..-66.0),
+            (Region::Madrid, 36.0..44.0, -10.0..4.0),
+            (Region::Warsaw, 49.0..55.0, 14.0..25.0),
+            (Region::Johannesburg, -35.0..-22.0, 16.0..33.0),
+            (Region::Tokyo, 24.0..46.0, 122.0..146.0),
+            (Region::Sydney, -44.0..-10.0, 113.0..154.0),
+        ];
+
+        for (region, lat, lon) in bounds {
+            let geo = region.city.geo;
+            assert!(lat.contains(&geo.y().raw()), "{region} latitude");
+            assert!(lon.contains(&geo.x().raw()), "{region} longitude");
+        }
+
+        let (lat, lon) = Region::Chicago.coordinates();
+        assert!((lat - 41.89).abs() < 0.01, "Chicago latitude: {lat}");
+        assert!((lon - -87.63).abs() < 0.01, "Chicago longitude: {lon}");
+        assert_eq!(lat, Region::Chicago.latitude());
+        assert_eq!(lon, Region::Chicago.longitude());
+
+        for (region, details) in Region::all() {
+            let (lon, lat) = (details.city.geo.x().raw(), details.city.geo.y().raw());
+            assert!((-90.0..=90.0).contains(&lat), "{region} latitude");
+            assert!((-180.0..=180.0).contains(&lon), "{region} longitude");
+
+            let hemisphere = match region.continent() {
+                Continent::NorthAmerica | Continent::SouthAmerica => lon < 0.0,
+                Continent::Europe => (-25.0..45.0).contains(&lon),
+                Continent::Africa | Continent::Asia | Continent::Oceania => lon > 0.0,
+            };
+            assert!(hemisphere, "{region} longitude is on the wrong continent");
+        }
+    }
+
+    #[test]
+    fn region_set() {
+        let mut set = RegionSet::new();
+        assert!(set.is_empty());
+
+        assert!(set.insert(Region::Chicago));
+        assert!(!set.insert(Region::Chicago));
+        assert!(set.insert(Region::Tokyo));
+        assert!(set.contains(Region::Chicago));
+        assert!(!set.contains(Region::Paris));
+        assert_eq!(2, set.len());
+
+        assert!(set.remove(Region::Chicago));
+        assert!(!set.remove(Region::Chicago));
+        assert_eq!(vec![Region::Tokyo], set.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn region_set_algebra() {
+        let a: RegionSet = [Region::Chicago, Region::Paris, Region::Tokyo]
+            .into_iter()
+            .collect();
+        let b: RegionSet = [Region::Paris, Region::Sydney].into_iter().collect();
+
+        assert_eq!(4, a.union(b).len());
+        assert_eq!(RegionSet::from(Region::Paris), a.intersection(b));
+        assert_eq!(
+            [Region::Chicago, Region::Tokyo]
+                .into_iter()
+                .collect::<RegionSet>(),
+            a.difference(b)
+        );
+    }
+
+    #[test]
+    fn region_set_all() {
+        let all = RegionSet::all();
+
+        assert_eq!(Region::all().count(), all.len());
+        assert!(Region::all().all(|(region, _)| all.contains(region)));
+        assert!(all.iter().zip(Region::all()).all(|(a, (b, _))| a == b));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn region_set_serde() {
+        let set: RegionSet = [Region::Chicago, Region::Amsterdam].into_iter().collect();
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(r#"["ams","ord"]"#, json);
+        assert_eq!(set, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn neighbors() {
+        let neighbors = Region::Chicago.neighbors(1500.0);
+
+        assert!(neighbors.contains(&Region::Dallas));
+        assert!(neighbors.contains(&Region::Secaucus));
+        assert!(!neighbors.contains(&Region::Tokyo));
+        assert!(!neighbors.contains(&Region::Chicago));
+
+        let distances: Vec<f64> = neighbors
+            .iter()
+            .map(|region| Region::Chicago.distance_to(*region))
+            .collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn point_conversions() {
+        use geo_types::Point;
+        use noisy_float::types::R32;
+
+        let point = Point::<R32>::from(Region::Paris);
+        assert!(
+            (point.y().raw() - 48.86).abs() < 0.01,
+            "latitude: {point:?}"
+        );
+        assert!(
+            (point.x().raw() - 2.35).abs() < 0.01,
+            "longitude: {point:?}"
+        );
+
+        let details = Region::Paris.details();
+        let (lat, lon) = <(f64, f64)>::from(&details);
+        assert_eq!((lat, lon), details.coordinates());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn detailed_region_serde() {
+        use super::DetailedRegion;
+
+        assert_eq!(
+            serde_json::json!("ord"),
+            serde_json::to_value(Region::Chicago).unwrap()
+        );
+
+        let json = serde_json::to_value(DetailedRegion(Region::Chicago)).unwrap();
+        assert_eq!("ord", json["code"]);
+        assert_eq!("Chicago, Illinois (US)", json["name"]);
+        assert_eq!("Chicago", json["city"]["name"]);
+        assert!(json["city"]["geo"].is_object(), "{json}");
+    }
 }
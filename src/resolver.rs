@@ -1,25 +1,39 @@
 use std::{
-    net::{Ipv6Addr, SocketAddr},
+    collections::HashMap,
+    net::{Ipv6Addr, SocketAddr, SocketAddrV6},
     ops::Deref,
     str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
+use futures::future::{BoxFuture, FutureExt, Shared};
 use hickory_resolver::{
     config::{NameServerConfig, NameServerConfigGroup, ResolverConfig, ResolverOpts},
+    error::ResolveError,
+    lookup::{Lookup, TxtLookup},
+    proto::rr::RecordType,
     IntoName, Name, TokioAsyncResolver,
 };
 
-use crate::{error::Error, placement::private_address, AppResolver, Location, Region};
+use crate::{error::Error, AppResolver, Location, Region};
 
 /// Query the Fly.io [internal DNS][] records.
 ///
 /// [internal DNS]: https://fly.io/docs/reference/private-networking/#fly-internal-addresses
 #[derive(Clone)]
-pub struct Resolver(pub(crate) TokioAsyncResolver);
+pub struct Resolver {
+    inner: TokioAsyncResolver,
+    cache: Option<Arc<Cache>>,
+    pub(crate) domain: Name,
+}
 
 impl Resolver {
     /// Create a [`Resolver`] which configures itself based on the host's
-    /// [detected][private_address] Fly.io [private network][] address.
+    /// [detected][crate::private_address] Fly.io [private network][] address.
+    ///
+    /// If `$FLYTRAP_DNS_SERVER` is set, queries are sent there instead, as with
+    /// [`Config::resolver`][crate::Config::resolver].
     ///
     /// If the host does not appear to be running under Fly.io or connected to
     /// the Wireguard VPN, an `Unavailable` [error][Error] will be returned.
@@ -28,19 +42,28 @@ impl Resolver {
     #[cfg(any(feature = "detect", feature = "environment"))]
     #[cfg_attr(docsrs, doc(cfg(any(feature = "detect", feature = "environment"))))]
     pub fn new() -> Result<Self, Error> {
-        use std::net::IpAddr;
+        #[cfg(feature = "environment")]
+        let config = crate::Config::from_env();
+        #[cfg(not(feature = "environment"))]
+        let config = crate::Config::default();
+
+        Self::from_config(&config)
+    }
 
-        let local = private_address().ok_or(Error::Unavailable)?;
+    /// Create a [`Resolver`] from `config`, detecting the private IP address
+    /// if `config` does not have one.
+    #[cfg(any(feature = "detect", feature = "environment"))]
+    fn from_config(config: &crate::Config) -> Result<Self, Error> {
+        let local = config.private_ip;
+        #[cfg(feature = "detect")]
+        let local = local.or_else(crate::placement::detect_address);
 
         #[cfg(feature = "environment")]
-        let hosted = crate::placement::hosted();
+        let hosted = config.hosted();
         #[cfg(not(feature = "environment"))]
         let hosted = true;
 
-        Ok(Self::with_source(
-            SocketAddr::new(IpAddr::V6(dns_server_address(local, hosted)), 53),
-            Some(SocketAddr::new(IpAddr::V6(local), 0)),
-        ))
+        config.resolver_from(local, hosted)
     }
 
     /// Create a [`Resolver`] which will send DNS queries to the given `source`
@@ -64,7 +87,7 @@ impl Resolver {
         sources: impl IntoIterator<Item = SocketAddr>,
         local: Option<SocketAddr>,
     ) -> Self {
-        Self(TokioAsyncResolver::tokio(
+        Self::from(TokioAsyncResolver::tokio(
             Self::config(sources.into_iter(), local),
             Self::options(),
         ))
@@ -76,7 +99,51 @@ impl Resolver {
     #[cfg_attr(docsrs, doc(cfg(feature = "system-resolver")))]
     pub fn system() -> Result<Self, Error> {
         let resolver = TokioAsyncResolver::tokio_from_system_conf().map_err(Error::from)?;
-        Ok(Self(resolver))
+        Ok(Self::from(resolver))
+    }
+
+    /// Reuse the results of DNS queries made through this [`Resolver`] (and
+    /// its [`AppResolver`]s) for `ttl`, regardless of the TTL of the records.
+    ///
+    /// Concurrent queries for the same name share a single lookup. Failed
+    /// lookups are not cached.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use flytrap::Resolver;
+    ///
+    /// # fn main() -> Result<(), flytrap::Error> {
+    /// let resolver = Resolver::new()?.with_cache(Duration::from_secs(5));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cache(self, ttl: Duration) -> Self {
+        Self {
+            cache: Some(Arc::new(Cache::new(ttl))),
+            ..self
+        }
+    }
+
+    /// Query names under `domain` (like `<app>.test.local`) instead of the
+    /// Fly.io `internal` domain; for use with self-hosted or test DNS zones.
+    ///
+    /// If `domain` is not a valid DNS name, a `Resolve` [error][Error] is
+    /// returned.
+    ///
+    /// ```
+    /// use std::net::SocketAddr;
+    /// use flytrap::Resolver;
+    ///
+    /// # fn main() -> Result<(), flytrap::Error> {
+    /// let server: SocketAddr = "127.0.0.1:5353".parse().unwrap();
+    /// let resolver = Resolver::with_source(server, None).with_domain("test.local")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_domain(self, domain: &str) -> Result<Self, Error> {
+        let domain = Name::from_ascii(domain).map_err(ResolveError::from)?;
+
+        Ok(Self { domain, ..self })
     }
 
     /// Create an [`AppResolver`] for querying the named app.
@@ -90,9 +157,30 @@ impl Resolver {
     #[cfg(feature = "environment")]
     #[cfg_attr(docsrs, doc(cfg(feature = "environment")))]
     pub fn current(&self) -> Result<AppResolver<'_>, Error> {
-        match std::env::var("FLY_APP_NAME") {
-            Ok(app) => Ok(self.app(app)),
-            _ => Err(Error::Unavailable),
+        let app = crate::Config::from_env().app.ok_or(Error::Unavailable)?;
+        Ok(self.app(app))
+    }
+
+    /// How long [`probe`][Resolver::probe] waits for a response.
+    pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Check that the Fly.io internal DNS server is reachable, by querying the
+    /// organization's `_apps` record (bypassing any [cache][Resolver::with_cache]).
+    ///
+    /// If no answer arrives within [`PROBE_TIMEOUT`][Resolver::PROBE_TIMEOUT],
+    /// a `Timeout` [error][Error] is returned. Each probe is a real DNS query,
+    /// and counts against Fly.io's DNS rate limits like any other; don't call
+    /// it more often than a readiness check needs.
+    pub async fn probe(&self) -> Result<(), Error> {
+        let query = Name::from_ascii("_apps")
+            .unwrap()
+            .append_domain(&self.domain)
+            .expect("invalid query");
+
+        match tokio::time::timeout(Self::PROBE_TIMEOUT, self.inner.txt_lookup(query)).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(err)) => Err(Error::from(err)),
+            Err(_) => Err(Error::Timeout),
         }
     }
 
@@ -117,15 +205,60 @@ impl Resolver {
             .collect()
     }
 
-    /// Perform an arbitrary `TXT` record query on the `.internal` domain.
+    /// Find all running [instances][Resolver::instances] of the named app.
+    pub async fn instances_for_app(&self, app: impl AsRef<str>) -> Result<Vec<Instance>, Error> {
+        let app = app.as_ref();
+        let mut instances = self.instances().await?;
+        instances.retain(|instance| instance.app == app);
+
+        Ok(instances)
+    }
+
+    /// Perform an arbitrary `TXT` record query on the `.internal` domain (or
+    /// the [configured][Resolver::with_domain] domain).
     pub async fn txt(&self, name: impl IntoName) -> Result<String, Error> {
         let query = name
             .into_name()
             .expect("invalid name")
-            .append_domain(&Name::from_ascii("internal").unwrap())
+            .append_domain(&self.domain)
             .expect("invalid query");
 
-        lookup_txt(&self.0, query).await
+        self.lookup_txt(query).await
+    }
+
+    /// Look up records of the given type, through the cache if one is
+    /// [enabled][Resolver::with_cache].
+    pub(crate) async fn lookup(
+        &self,
+        name: Name,
+        record_type: RecordType,
+    ) -> Result<Lookup, ResolveError> {
+        match &self.cache {
+            Some(cache) => cache.lookup(&self.inner, name, record_type).await,
+            None => self.inner.lookup(name, record_type).await,
+        }
+    }
+
+    /// Look up a `TXT` record, concatenating all its strings.
+    pub(crate) async fn lookup_txt(&self, query: Name) -> Result<String, Error> {
+        let results = TxtLookup::from(self.lookup(query, RecordType::TXT).await?);
+
+        let length: usize = results
+            .iter()
+            .flat_map(|r| r.iter().map(|item| item.len()))
+            .sum();
+
+        let mut value = String::with_capacity(length);
+
+        for result in results {
+            for item in result.iter() {
+                if let Ok(text) = std::str::from_utf8(item) {
+                    value.push_str(text);
+                }
+            }
+        }
+
+        Ok(value)
     }
 
     fn config(
@@ -160,32 +293,71 @@ impl Resolver {
 
 impl From<TokioAsyncResolver> for Resolver {
     fn from(value: TokioAsyncResolver) -> Self {
-        Self(value)
+        Self {
+            inner: value,
+            cache: None,
+            domain: Name::from_ascii("internal").unwrap(),
+        }
     }
 }
 
-pub(crate) async fn lookup_txt(
-    resolver: &TokioAsyncResolver,
-    query: Name,
-) -> Result<String, Error> {
-    let results = resolver.txt_lookup(query).await.map_err(Error::from)?;
+type SharedLookup = Shared<BoxFuture<'static, Result<Lookup, ResolveError>>>;
+
+/// DNS lookups shared between callers for a fixed TTL.
+struct Cache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(Name, RecordType), (Instant, SharedLookup)>>,
+}
+
+impl Cache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn lookup(
+        &self,
+        resolver: &TokioAsyncResolver,
+        name: Name,
+        record_type: RecordType,
+    ) -> Result<Lookup, ResolveError> {
+        let key = (name, record_type);
+
+        let lookup = {
+            let mut entries = self.entries.lock().unwrap();
+            let now = Instant::now();
+
+            match entries.get(&key) {
+                Some((created, lookup)) if now.duration_since(*created) < self.ttl => {
+                    lookup.clone()
+                }
+                _ => {
+                    entries.retain(|_, (created, _)| now.duration_since(*created) < self.ttl);
 
-    let length: usize = results
-        .iter()
-        .flat_map(|r| r.iter().map(|item| item.len()))
-        .sum();
+                    let (resolver, (name, record_type)) = (resolver.clone(), key.clone());
+                    let lookup = async move { resolver.lookup(name, record_type).await }
+                        .boxed()
+                        .shared();
 
-    let mut value = String::with_capacity(length);
+                    entries.insert(key.clone(), (now, lookup.clone()));
+                    lookup
+                }
+            }
+        };
+
+        let result = lookup.clone().await;
 
-    for result in results {
-        for item in result.iter() {
-            if let Ok(text) = std::str::from_utf8(item) {
-                value.push_str(text);
+        if result.is_err() {
+            let mut entries = self.entries.lock().unwrap();
+            if matches!(entries.get(&key), Some((_, cached)) if cached.ptr_eq(&lookup)) {
+                entries.remove(&key);
             }
         }
-    }
 
-    Ok(value)
+        result
+    }
 }
 
 /// A Fly.io [machine][] with an ID and [region][Region].
@@ -213,8 +385,16 @@ pub(crate) async fn lookup_txt(
 /// ```
 #[derive(PartialOrd, Ord, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[non_exhaustive]
 pub struct Node {
-    #[cfg_attr(feature = "serde", serde(rename = "region"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "region",
+            serialize_with = "serialize_location",
+            deserialize_with = "deserialize_location"
+        )
+    )]
     pub location: Location,
     pub id: String,
 }
@@ -276,11 +456,36 @@ impl std::hash::Hash for Node {
     }
 }
 
+/// Serialize a [`Location`] as its region code.
+#[cfg(feature = "serde")]
+fn serialize_location<S: serde::Serializer>(
+    location: &Location,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(location)
+}
+
+/// Deserialize a [`Location`] by [parsing][FromStr] its region code.
+///
+/// Reading the code as a string (rather than through `Location`'s `untagged`
+/// representation) keeps `"region"` unambiguous when `Node` is flattened into
+/// [`Peer`] and [`Instance`].
+#[cfg(feature = "serde")]
+fn deserialize_location<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Location, D::Error> {
+    use serde::{de::Error as _, Deserialize};
+
+    let code = std::borrow::Cow::<'de, str>::deserialize(deserializer)?;
+    code.parse().map_err(D::Error::custom)
+}
+
 /// A fully-resolved [`Node`] whose private IP address is known.
 ///
 /// Returned from [`AppResolver::peers`][crate::AppResolver::peers].
 #[derive(PartialOrd, Ord, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[non_exhaustive]
 pub struct Peer {
     #[cfg_attr(feature = "serde", serde(flatten))]
     pub node: Node,
@@ -294,6 +499,57 @@ impl Peer {
             private_ip,
         }
     }
+
+    /// Describe this peer as a list of key-value attributes, for attaching
+    /// to tracing spans or metrics.
+    ///
+    /// ```
+    /// use flytrap::{Peer, Region};
+    ///
+    /// let peer = Peer::new(Region::Tokyo, "148e21dad76789", "fdaa::3".parse().unwrap());
+    ///
+    /// assert_eq!(
+    ///     peer.attributes(),
+    ///     [
+    ///         ("peer.id", "148e21dad76789".to_owned()),
+    ///         ("peer.region", "nrt".to_owned()),
+    ///         ("peer.private_ip", "fdaa::3".to_owned()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn attributes(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("peer.id", self.id.clone()),
+            ("peer.region", self.location.to_string()),
+            ("peer.private_ip", self.private_ip.to_string()),
+        ]
+    }
+
+    /// Check if a TCP connection can be opened to this peer's private IP
+    /// address on the given `port`, waiting at most `timeout`.
+    ///
+    /// This blocks the current thread; from async code, call it inside
+    /// [`spawn_blocking`](https://docs.rs/tokio/latest/tokio/task/fn.spawn_blocking.html).
+    #[cfg(feature = "reachability")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "reachability")))]
+    pub fn is_reachable_sync(&self, port: u16, timeout: Duration) -> bool {
+        std::net::TcpStream::connect_timeout(&self.socket_addr(port).into(), timeout).is_ok()
+    }
+
+    /// The socket address of the given `port` at this peer's private IP
+    /// address.
+    ///
+    /// ```
+    /// use flytrap::{Peer, Region};
+    ///
+    /// let peer = Peer::new(Region::Tokyo, "148e21dad76789", "fdaa::3".parse().unwrap());
+    ///
+    /// assert_eq!(peer.socket_addr(8080).to_string(), "[fdaa::3]:8080");
+    /// ```
+    #[inline]
+    pub const fn socket_addr(&self, port: u16) -> SocketAddrV6 {
+        SocketAddrV6::new(self.private_ip, port, 0, 0)
+    }
 }
 
 impl Deref for Peer {
@@ -343,8 +599,13 @@ impl std::hash::Hash for Peer {
 /// # Ok(())
 /// # }
 /// ```
+///
+/// When parsing, the `instance`, `app`, `ip`, and `region` fields are required
+/// and may appear in any order. Other fields, empty segments, and whitespace
+/// around fields are ignored; a value may itself contain `=`.
 #[derive(PartialOrd, Ord, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[non_exhaustive]
 pub struct Instance {
     pub app: String,
     #[cfg_attr(feature = "serde", serde(flatten))]
@@ -363,6 +624,13 @@ impl Instance {
             peer: Peer::new(region, id, private_ip),
         }
     }
+
+    /// Return this instance’s [`Region`], if its region code was recognized.
+    #[cfg(feature = "regions")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "regions")))]
+    pub const fn region(&self) -> Option<Region> {
+        self.peer.node.location.region()
+    }
 }
 
 impl Deref for Instance {
@@ -383,7 +651,7 @@ impl FromStr for Instance {
         let mut location: Option<Location> = None;
 
         for field in s.split(',') {
-            match field.split_once('=') {
+            match field.trim().split_once('=') {
                 Some(("instance", value)) => id = Some(value),
                 Some(("app", value)) => app = Some(value),
                 Some(("ip", value)) => match value.parse::<Ipv6Addr>() {
@@ -392,6 +660,9 @@ impl FromStr for Instance {
                 },
                 Some(("region", value)) => match value.parse::<Location>() {
                     Ok(value) => location = Some(value),
+                    #[cfg(feature = "regions")]
+                    Err(err) => return Err(Error::from(err)),
+                    #[cfg(not(feature = "regions"))]
                     Err(_) => return Err(Error::Parse),
                 },
                 _ => continue,
@@ -449,19 +720,44 @@ impl std::hash::Hash for Instance {
 /// # }
 /// ```
 pub fn dns_server_address(local: impl Into<Ipv6Addr>, hosted: bool) -> Ipv6Addr {
-    let [a, b, c, _, _, _, _, _] = local.into().segments();
-    assert_eq!(a, 0xfdaa);
+    try_dns_server_address(local, hosted).expect("not a Fly.io private networking address")
+}
+
+/// Return the Fly.io DNS server address which serves a given `local` Fly.io
+/// Wireguard address, or an [`Address`][Error::Address] error if `local` does
+/// not appear to be a Fly.io private networking address.
+///
+/// Unlike [`dns_server_address`], this is suitable for addresses which were not
+/// detected on this machine (e.g., from user input).
+///
+/// ```
+/// # use std::net::Ipv6Addr;
+/// # fn main() -> Result<(), std::net::AddrParseError> {
+/// let address: Ipv6Addr = "fdaa:0:18:a7b:d6b:0:a:2".parse()?;
+/// assert_eq!("fdaa::3", flytrap::try_dns_server_address(address, true).unwrap().to_string());
+///
+/// let address: Ipv6Addr = "2001:db8::1".parse()?;
+/// assert!(flytrap::try_dns_server_address(address, true).is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn try_dns_server_address(local: impl Into<Ipv6Addr>, hosted: bool) -> Result<Ipv6Addr, Error> {
+    let local = local.into();
+    let [a, b, c, _, _, _, _, _] = local.segments();
+    if a != 0xfdaa {
+        return Err(Error::Address(local));
+    }
 
     let (b, c) = if hosted { (0, 0) } else { (b, c) };
 
-    Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 3)
+    Ok(Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 3))
 }
 
 #[cfg(test)]
 mod test {
-    use std::net::Ipv6Addr;
+    use std::{net::Ipv6Addr, time::Duration};
 
-    use super::{dns_server_address, Node};
+    use super::{dns_server_address, try_dns_server_address, Instance, Node, Peer, Resolver};
 
     #[test]
     fn test_dns_server_address() {
@@ -471,6 +767,13 @@ mod test {
 
         assert_eq!(external, dns_server_address(address, false));
         assert_eq!(hosted, dns_server_address(address, true));
+
+        assert_eq!(external, try_dns_server_address(address, false).unwrap());
+        let other = "2001:db8::1".parse::<Ipv6Addr>().unwrap();
+        assert!(matches!(
+            try_dns_server_address(other, true),
+            Err(crate::Error::Address(addr)) if addr == other
+        ));
     }
 
     #[test]
@@ -489,4 +792,302 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_parse_instance_fields() {
+        use crate::Error;
+
+        let expected: Instance =
+            "instance=148e21dad76789,app=flytrap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=sea"
+                .parse()
+                .unwrap();
+
+        for result in [
+            "region=sea,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,app=flytrap,instance=148e21dad76789",
+            "instance=148e21dad76789,app=flytrap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=sea,",
+            "instance=148e21dad76789,,app=flytrap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=sea,,",
+            "instance=148e21dad76789, app=flytrap, ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2, region=sea",
+            "instance=148e21dad76789,app=flytrap,volume,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=sea",
+            "instance=148e21dad76789,meta=a=b,app=flytrap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=sea",
+        ] {
+            let instance: Instance = result.parse().unwrap();
+            assert_eq!(expected, instance, "{result}");
+            assert_eq!("flytrap", instance.app, "{result}");
+            assert_eq!(expected.private_ip, instance.private_ip, "{result}");
+            assert_eq!(expected.location, instance.location, "{result}");
+        }
+
+        let instance: Instance =
+            "instance=148e21dad76789,app=fly=trap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=sea"
+                .parse()
+                .unwrap();
+        assert_eq!("fly=trap", instance.app);
+
+        for result in [
+            "",
+            "instance=148e21dad76789,app=flytrap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2",
+            "instance=148e21dad76789,app=flytrap,region=sea",
+            "app=flytrap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=sea",
+            "instance=148e21dad76789,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=sea",
+            "instance=148e21dad76789,app=flytrap,ip=10.0.0.1,region=sea",
+        ] {
+            assert!(
+                matches!(result.parse::<Instance>(), Err(Error::Parse)),
+                "{result}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "regions")]
+    fn test_constructors() {
+        use crate::Region;
+
+        let ip = "fdaa:2:224b:a7b:2dbb:3e15:aaea:2"
+            .parse::<Ipv6Addr>()
+            .unwrap();
+
+        let node = Node::new(Region::Seattle, "148e21dad76789");
+        let peer = Peer::new(Region::Seattle, "148e21dad76789", ip);
+        let instance = Instance::new("flytrap", "148e21dad76789", Region::Seattle, ip);
+
+        assert_eq!(node.clone().into_peer(ip), peer);
+        assert_eq!(peer.node, node);
+        assert_eq!(instance.peer, peer);
+        assert_eq!(instance.app, "flytrap");
+        assert_eq!(instance.region(), Some(Region::Seattle));
+        assert_eq!(instance.private_ip, ip);
+    }
+
+    #[test]
+    #[cfg(feature = "regions")]
+    fn test_peer_attributes() {
+        use std::collections::HashMap;
+
+        let peer: Peer = Node::new("cai".parse::<crate::Location>().unwrap(), "4d89699c030518")
+            .into_peer("fdaa:0:18:a7b:d6b:0:a:2".parse().unwrap());
+        let attributes: HashMap<_, _> = peer.attributes().into_iter().collect();
+
+        assert_eq!(3, attributes.len());
+        assert_eq!("4d89699c030518", attributes["peer.id"]);
+        assert_eq!("cai", attributes["peer.region"]);
+        assert_eq!("fdaa:0:18:a7b:d6b:0:a:2", attributes["peer.private_ip"]);
+    }
+
+    #[test]
+    fn test_peer_socket_addr() {
+        use std::net::SocketAddr;
+
+        let ip = "fdaa:0:18:a7b:d6b:0:a:2".parse::<Ipv6Addr>().unwrap();
+        let peer =
+            Node::new("sea".parse::<crate::Location>().unwrap(), "148e21dad76789").into_peer(ip);
+
+        let addr = peer.socket_addr(4280);
+        assert_eq!(ip, *addr.ip());
+        assert_eq!(4280, addr.port());
+        assert_eq!(
+            "[fdaa:0:18:a7b:d6b:0:a:2]:4280"
+                .parse::<SocketAddr>()
+                .unwrap(),
+            SocketAddr::from(addr)
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "reachability", feature = "regions"))]
+    fn test_peer_reachable() {
+        use std::net::TcpListener;
+
+        use crate::Region;
+
+        // Some sandboxes have no IPv6 loopback; there's nothing to test there.
+        let Ok(listener) = TcpListener::bind((Ipv6Addr::LOCALHOST, 0)) else {
+            return;
+        };
+        let port = listener.local_addr().unwrap().port();
+        let peer = Peer::new(Region::Seattle, "148e21dad76789", Ipv6Addr::LOCALHOST);
+        let timeout = Duration::from_millis(500);
+
+        assert!(peer.is_reachable_sync(port, timeout));
+
+        drop(listener);
+        assert!(!peer.is_reachable_sync(port, timeout));
+    }
+
+    #[test]
+    #[cfg(all(feature = "regions", feature = "serde"))]
+    fn test_deserialize_peer() {
+        use crate::{Location, Region};
+
+        let ip = "fdaa:0:18:a7b:d6b:0:a:2".parse::<Ipv6Addr>().unwrap();
+
+        let peer: Peer = serde_json::from_str(
+            r#"{"region": "ord", "id": "148e21dad76789", "private_ip": "fdaa:0:18:a7b:d6b:0:a:2"}"#,
+        )
+        .unwrap();
+        assert_eq!(Location::Region(Region::Chicago), peer.location);
+        assert_eq!(ip, peer.private_ip);
+
+        let peer: Peer = serde_json::from_str(
+            r#"{"region": "cai", "id": "4d89699c030518", "private_ip": "fdaa:0:18:a7b:d6b:0:a:2"}"#,
+        )
+        .unwrap();
+        assert_eq!(Location::Unknown("cai".parse().unwrap()), peer.location);
+
+        let json = serde_json::to_string(&peer).unwrap();
+        assert!(json.contains(r#""region":"cai""#), "{json}");
+        assert_eq!(peer, serde_json::from_str::<Peer>(&json).unwrap());
+
+        let instance: Instance = serde_json::from_str(
+            r#"{"app": "flytrap", "region": "ord", "id": "148e21dad76789", "private_ip": "fdaa:0:18:a7b:d6b:0:a:2"}"#,
+        )
+        .unwrap();
+        assert_eq!(Some(Region::Chicago), instance.region());
+
+        assert!(serde_json::from_str::<Peer>(
+            r#"{"region": "ORD!", "id": "148e21dad76789", "private_ip": "fdaa:0:18:a7b:d6b:0:a:2"}"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "regions")]
+    fn test_parse_instance_region_error() {
+        use std::error::Error as _;
+
+        use crate::{Error, Location, RegionError};
+
+        let instance: Instance =
+            "instance=148e21dad76789,app=flytrap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=cai"
+                .parse()
+                .unwrap();
+        assert_eq!(Location::Unknown("cai".parse().unwrap()), instance.location);
+
+        let err =
+            "instance=148e21dad76789,app=flytrap,ip=fdaa:2:224b:a7b:2dbb:3e15:aaea:2,region=!!"
+                .parse::<Instance>()
+                .unwrap_err();
+        assert!(matches!(err, Error::Region(RegionError::Invalid)));
+        assert_eq!(
+            Some(&RegionError::Invalid),
+            err.source().and_then(|source| source.downcast_ref())
+        );
+
+        assert!(matches!(
+            "148e21dad76789 !!".parse::<Node>(),
+            Err(Error::Region(RegionError::Invalid))
+        ));
+        assert!(matches!(
+            Error::from(RegionError::Unrecognized),
+            Error::Region(RegionError::Unrecognized)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_instances_for_app() {
+        use crate::mock::{txt, DnsServer};
+
+        let server = DnsServer::start(vec![txt(
+            "_instances.internal",
+            "instance=148e21dad76789,app=flytrap,ip=fdaa:0:1:a7b:1c4::2,region=sea;\
+             instance=4d89699c030518,app=other,ip=fdaa:0:1:a7b:1c5::3,region=ams;\
+             instance=6e82de14c35038,app=flytrap,ip=fdaa:0:1:a7b:1c6::4,region=ams",
+        )])
+        .await;
+        let resolver = server.resolver();
+
+        let instances = resolver.instances().await.unwrap();
+        assert_eq!(3, instances.len());
+
+        let instances = resolver.instances_for_app("flytrap").await.unwrap();
+        let ids: Vec<&str> = instances.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(vec!["148e21dad76789", "6e82de14c35038"], ids);
+
+        #[cfg(feature = "regions")]
+        {
+            use crate::Region;
+
+            let in_ams: Vec<&Instance> = instances
+                .iter()
+                .filter(|i| i.region() == Some(Region::Amsterdam))
+                .collect();
+            assert_eq!(1, in_ams.len());
+            assert_eq!("6e82de14c35038", in_ams[0].id);
+        }
+
+        assert!(resolver
+            .instances_for_app("missing")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "detect", feature = "environment"))]
+    async fn test_new_from_config() {
+        use crate::{
+            mock::{txt, DnsServer},
+            Config, Error,
+        };
+
+        let server = DnsServer::start(vec![txt("_apps.internal", "flytrap")]).await;
+        let addr = server.addr().to_string();
+        let config = Config::from_vars([
+            ("FLY_APP_NAME", "flytrap"),
+            ("FLY_PRIVATE_IP", "fdaa:0:18:a7b:d6b:0:a:2"),
+            ("FLYTRAP_DNS_SERVER", addr.as_str()),
+        ]);
+
+        Resolver::from_config(&config)
+            .unwrap()
+            .probe()
+            .await
+            .unwrap();
+        config.resolver().unwrap().probe().await.unwrap();
+        assert_eq!(vec!["_apps.internal. TXT"; 2], server.queries());
+
+        let config = Config::from_vars([("FLY_PRIVATE_IP", "2001:db8::1")]);
+        assert!(matches!(
+            Resolver::from_config(&config),
+            Err(Error::Address(_))
+        ));
+        assert!(matches!(config.resolver(), Err(Error::Address(_))));
+    }
+
+    #[tokio::test]
+    async fn test_probe() {
+        use crate::mock::{txt, DnsServer};
+
+        let server = DnsServer::start(vec![txt("_apps.internal", "flytrap,other")]).await;
+        let resolver = server.resolver().with_cache(Duration::from_secs(60));
+
+        resolver.probe().await.unwrap();
+        resolver.probe().await.unwrap();
+        assert_eq!(
+            vec!["_apps.internal. TXT", "_apps.internal. TXT"],
+            server.queries()
+        );
+
+        let server = DnsServer::start(vec![]).await;
+        assert!(server.resolver().probe().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_probe_timeout() {
+        use crate::{
+            mock::{txt, DnsServer},
+            Error,
+        };
+
+        let server = DnsServer::start_delayed(
+            vec![txt("_apps.internal", "flytrap")],
+            Resolver::PROBE_TIMEOUT + Duration::from_secs(1),
+        )
+        .await;
+
+        assert!(matches!(
+            server.resolver().probe().await,
+            Err(Error::Timeout)
+        ));
+    }
 }